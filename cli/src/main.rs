@@ -1,8 +1,125 @@
-use std::io::{self, Read};
+use std::env;
+use std::io;
+use std::process::ExitCode;
 
-fn main() {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input).unwrap();
+use fjson_core::{
+    BareWords, Dialect, DuplicateKeys, FixOptions, LineEnding, NonFinite, NumberFormat, Undefined,
+};
 
-    print!("{}", fjson_core::fix(input));
+const USAGE: &str = "\
+Usage: fjson-cli [OPTIONS] < input.json
+
+Reads JSON from stdin, fixes it and writes it to stdout.
+
+Options:
+      --dialect <NAME>
+                      Syntax of the input: json (default), json5,
+                      javascript (or js), python, ruby, java or go
+      --omit-undefined
+                      Leave out object members that are undefined (javascript)
+      --non-finite-null
+                      Print NaN and Infinity as null instead of strings
+      --drop-bare-words
+                      Leave out bare words that aren't literals (ok) instead of
+                      quoting them
+  -m, --minify        Print on a single line without whitespace
+  -i, --indent <N>    Indent with N spaces (default: 3)
+  -t, --tab           Indent with tabs
+  -w, --width <N>     Keep arrays and objects that fit in N columns on one line
+  -n, --newline       End the output with a newline
+      --crlf          Use \\r\\n line endings
+      --leading-zeros Keep digits with leading zeros (ZIP codes, IDs) as strings
+      --canonical-numbers
+                      Print numbers in their shortest round-trip form (1.50 -> 1.5)
+      --big-int-strings
+                      Quote integers beyond 2^53 so JavaScript keeps every digit
+  -d, --duplicate-keys <POLICY>
+                      Handle repeated object keys: last (default), first,
+                      all (array of the values) or rename (key#2, key#3...)
+  -h, --help          Print this help";
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<FixOptions>, String> {
+    let mut options = FixOptions::new();
+
+    while let Some(arg) = args.next() {
+        options = match arg.as_str() {
+            "--dialect" => {
+                let dialect = match args.next().as_deref() {
+                    Some("json") => Dialect::Json,
+                    Some("json5") => Dialect::Json5,
+                    Some("javascript" | "js") => Dialect::JavaScript,
+                    Some("python") => Dialect::Python,
+                    Some("ruby") => Dialect::Ruby,
+                    Some("java") => Dialect::Java,
+                    Some("go") => Dialect::Go,
+                    _ => {
+                        return Err(format!(
+                            "{arg} expects json, json5, javascript, python, ruby, java or go"
+                        ));
+                    }
+                };
+                options.dialect(dialect)
+            }
+            "--omit-undefined" => options.undefined(Undefined::Omit),
+            "--non-finite-null" => options.non_finite(NonFinite::Null),
+            "--drop-bare-words" => options.bare_words(BareWords::Drop),
+            "-m" | "--minify" => options.minify(true),
+            "-i" | "--indent" => {
+                let width = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| format!("{arg} expects a number of spaces"))?;
+                options.indent(" ".repeat(width))
+            }
+            "-t" | "--tab" => options.indent("\t"),
+            "-w" | "--width" => {
+                let width = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| format!("{arg} expects a number of columns"))?;
+                options.max_width(width)
+            }
+            "-n" | "--newline" => options.trailing_newline(true),
+            "--crlf" => options.line_ending(LineEnding::CrLf),
+            "--leading-zeros" => options.leading_zeros_as_strings(true),
+            "--canonical-numbers" => options.number_format(NumberFormat::Canonical),
+            "--big-int-strings" => options.big_integers_as_strings(true),
+            "-d" | "--duplicate-keys" => {
+                let policy = match args.next().as_deref() {
+                    Some("last") => DuplicateKeys::LastWins,
+                    Some("first") => DuplicateKeys::FirstWins,
+                    Some("all") => DuplicateKeys::KeepAll,
+                    Some("rename") => DuplicateKeys::Rename,
+                    _ => return Err(format!("{arg} expects last, first, all or rename")),
+                };
+                options.duplicate_keys(policy)
+            }
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("unknown option {arg:?}")),
+        };
+    }
+
+    Ok(Some(options))
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("fjson: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match fjson_core::fix_reader_to_writer_with(io::stdin().lock(), io::stdout().lock(), &options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("fjson: {err}");
+            ExitCode::FAILURE
+        }
+    }
 }
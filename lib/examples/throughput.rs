@@ -0,0 +1,137 @@
+//! Measures `fix` throughput on large generated inputs.
+//!
+//! ```sh
+//! cargo run --release -p fjson-core --example throughput [size_mb] [fixture]
+//! ```
+//!
+//! On Linux, the peak resident memory of the process is printed too, so run
+//! one fixture at a time to compare memory use.
+
+use std::time::Instant;
+
+fn logs(size: usize) -> String {
+    let mut input = String::from("[");
+    let mut i = 0;
+    while input.len() < size {
+        input.push_str(&format!(
+            r#"{{"id": {i}, "level": "info", "ts": 1700000000.{i}, "ok": true, "msg": "request handled in {i}ms", "tags": ["api", "v2", null]}},"#
+        ));
+        i += 1;
+    }
+    input.push(']');
+    input
+}
+
+fn unicode(size: usize) -> String {
+    let mut input = String::from("[");
+    while input.len() < size {
+        input.push_str(r#""Olá, 世界! Привет 👋 — ünïcødé text","#);
+    }
+    input.push(']');
+    input
+}
+
+fn truncated(size: usize) -> String {
+    let mut input = logs(size);
+    input.truncate(input.len() * 2 / 3);
+    while !input.is_char_boundary(input.len()) {
+        input.pop();
+    }
+    input
+}
+
+fn wide(size: usize) -> String {
+    let mut input = String::from("{");
+    let mut i = 0;
+    while input.len() < size {
+        input.push_str(&format!(r#""key_{i}": {i}, "#));
+        i += 1;
+    }
+    input.push('}');
+    input
+}
+
+/// A wide object whose keys repeat, so every insert after the first round replaces a key.
+fn duplicates(size: usize) -> String {
+    let mut input = String::from("{");
+    let mut i = 0;
+    while input.len() < size {
+        input.push_str(&format!(r#""key_{}": {i}, "#, i % 50_000));
+        i += 1;
+    }
+    input.push('}');
+    input
+}
+
+fn main() {
+    let size = std::env::args()
+        .nth(1)
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(50)
+        * 1024
+        * 1024;
+
+    let fixture = std::env::args().nth(2);
+
+    type Generate = fn(usize) -> String;
+    let fixtures: [(&str, Generate); 5] = [
+        ("logs", logs),
+        ("unicode", unicode),
+        ("truncated", truncated),
+        ("wide", wide),
+        ("duplicates", duplicates),
+    ];
+
+    for (name, generate) in fixtures {
+        if fixture.as_deref().is_some_and(|f| f != name) {
+            continue;
+        }
+
+        let input = generate(size);
+        let mb = input.len() as f64 / (1024.0 * 1024.0);
+
+        let start = Instant::now();
+        let json = fjson_core::fix_value(input.as_str());
+        let parsed = start.elapsed().as_secs_f64();
+        let parse_peak = peak_memory();
+        let output = json.to_string();
+        let total = start.elapsed().as_secs_f64();
+
+        // fed in 64-byte chunks, with a snapshot of the value after each one
+        let start = Instant::now();
+        let mut stream = fjson_core::StreamParser::new();
+        let mut rest = input.as_str();
+        while !rest.is_empty() {
+            let mut end = rest.len().min(64);
+            while !rest.is_char_boundary(end) {
+                end += 1;
+            }
+            stream.feed(&rest[..end]);
+            stream.value();
+            rest = &rest[end..];
+        }
+        let streamed = start.elapsed().as_secs_f64();
+
+        println!(
+            "{name:>10}: {mb:>6.1} MB | parse {:>7.1} MB/s | parse + print {:>7.1} MB/s | stream + value {:>7.1} MB/s | {} bytes out",
+            mb / parsed,
+            mb / total,
+            mb / streamed,
+            output.len()
+        );
+
+        if let (Some(parse_peak), Some(peak)) = (parse_peak, peak_memory()) {
+            println!(
+                "{:>10}  peak memory: {parse_peak} after parse, {peak} after print",
+                ""
+            );
+        }
+    }
+}
+
+/// Peak resident memory of the process (Linux only).
+fn peak_memory() -> Option<String> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    Some(line["VmHWM:".len()..].trim().to_string())
+}
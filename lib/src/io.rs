@@ -0,0 +1,80 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, BufWriter, ErrorKind, Read, Write};
+use std::str;
+
+use crate::parser::Parser;
+use crate::{FixOptions, Json};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Parses everything `reader` yields chunk by chunk, so the raw input is never held in full.
+///
+/// A multi-byte character cut off at the end of the input is dropped like any other
+/// truncation, but invalid UTF-8 anywhere else is an [`ErrorKind::InvalidData`] error.
+pub(crate) fn read_value<R: Read>(
+    mut reader: R,
+    options: &FixOptions,
+) -> io::Result<Json<'static>> {
+    let mut parser = Parser::streaming(Cow::Borrowed(options));
+    let mut buf = vec![0; CHUNK_SIZE];
+    // bytes of an incomplete character carried over from the previous read
+    let mut carry = 0;
+
+    loop {
+        let n = match reader.read(&mut buf[carry..]) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        let len = carry + n;
+
+        let valid = match str::from_utf8(&buf[..len]) {
+            Ok(_) => len,
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => return Err(io::Error::new(ErrorKind::InvalidData, err)),
+        };
+
+        parser.feed(str::from_utf8(&buf[..valid]).expect("validated above"));
+        buf.copy_within(valid..len, 0);
+        carry = len - valid;
+    }
+
+    let json = parser.parse_and_fix();
+    Ok(crate::deserialize(json, options))
+}
+
+/// Prints `json` into `writer` through a buffer.
+pub(crate) fn write_value<W: Write>(
+    json: &Json,
+    writer: W,
+    options: &FixOptions,
+) -> io::Result<()> {
+    let mut w = IoWriter {
+        inner: BufWriter::new(writer),
+        error: None,
+    };
+
+    match json.write_to(&mut w, options) {
+        Ok(()) => w.inner.flush(),
+        Err(fmt::Error) => Err(w
+            .error
+            .unwrap_or_else(|| io::Error::other("formatter error"))),
+    }
+}
+
+/// Adapts an [`io::Write`] to [`fmt::Write`], keeping the underlying error.
+struct IoWriter<W: Write> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
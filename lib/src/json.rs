@@ -0,0 +1,506 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::ControlFlow;
+
+use crate::number::{self, Decimal, NumberError};
+use crate::parser::Parser;
+use crate::{FixOptions, Map};
+
+fn write_escaped<W: fmt::Write>(w: &mut W, s: &str) -> fmt::Result {
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if c >= ' ' && c != '"' && c != '\\' {
+            continue;
+        }
+
+        w.write_str(&s[start..i])?;
+        match c {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            '\u{0008}' => w.write_str("\\b")?,
+            '\u{000C}' => w.write_str("\\f")?,
+            ch => write!(w, "\\u{:04x}", ch as u32)?,
+        }
+        start = i + c.len_utf8();
+    }
+    w.write_str(&s[start..])
+}
+
+/// Width of `s` once escaped and quoted, in characters.
+fn quoted_width(s: &str) -> usize {
+    let escapes: usize = s
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' | '\n' | '\r' | '\t' | '\u{0008}' | '\u{000C}' => 1,
+            ch if ch < ' ' => 5,
+            _ => 0,
+        })
+        .sum();
+    s.chars().count() + escapes + 2
+}
+
+fn write_indent<W: fmt::Write>(w: &mut W, indent: &str, tabs: usize) -> fmt::Result {
+    for _ in 0..tabs {
+        w.write_str(indent)?;
+    }
+    Ok(())
+}
+
+/// A repaired JSON value.
+///
+/// Numbers keep their normalized lexeme and objects keep their keys
+/// in insertion order.
+///
+/// Strings, keys and number lexemes borrow from the input when they needed no
+/// unescaping or repair (see [`fix_value_borrowed`](crate::fix_value_borrowed)).
+/// `Json<'static>` owns all of its data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json<'a> {
+    Null,
+    True,
+    False,
+    Number(Cow<'a, str>),
+    String(Cow<'a, str>),
+    Array(Vec<Json<'a>>),
+    Object(Map<'a>),
+}
+
+impl<'a> Json<'a> {
+    /// Returns `true` if the value is `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the boolean if the value is `true` or `false`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::True => Some(true),
+            Self::False => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Returns the number lexeme (e.g. `"-1.5e3"`) if the value is a number.
+    pub fn as_number(&self) -> Option<&str> {
+        match self {
+            Self::Number(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the value is a number without a fractional part (e.g. `3`, `1.0` or `1e3`).
+    pub fn is_integer(&self) -> bool {
+        self.as_number().is_some_and(number::is_integer)
+    }
+
+    /// Returns the number if it is an integer that fits in an `i64`.
+    ///
+    /// Use `i64::try_from(&json)` to know why it doesn't.
+    pub fn as_i64(&self) -> Option<i64> {
+        i64::try_from(self).ok()
+    }
+
+    /// Returns the number if it is an integer that fits in a `u64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        u64::try_from(self).ok()
+    }
+
+    /// Returns the number if it is an integer that fits in an `i128`.
+    pub fn as_i128(&self) -> Option<i128> {
+        i128::try_from(self).ok()
+    }
+
+    /// Returns the number if an `f64` holds it exactly, i.e. the double prints back
+    /// to the same decimal value (`0.1` does, `12345678901234567890` doesn't).
+    pub fn as_f64(&self) -> Option<f64> {
+        f64::try_from(self).ok()
+    }
+
+    /// Returns the exact value of the number, if its significant digits fit in an `i128`.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        Decimal::try_from(self).ok()
+    }
+
+    /// Returns the string contents if the value is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements if the value is an array.
+    pub fn as_array(&self) -> Option<&[Json<'a>]> {
+        match self {
+            Self::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if the value is an object.
+    pub fn as_object(&self) -> Option<&Map<'a>> {
+        match self {
+            Self::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
+
+    /// Returns the value stored under `key` if the value is an object.
+    pub fn get(&self, key: &str) -> Option<&Json<'a>> {
+        self.as_object()?.get(key)
+    }
+
+    /// Returns the object entries in their original order if the value is an object.
+    pub fn entries(&self) -> Option<impl Iterator<Item = (&str, &Json<'a>)>> {
+        self.as_object().map(Map::iter)
+    }
+
+    /// Copies any borrowed data, detaching the value from the input.
+    pub fn into_owned(self) -> Json<'static> {
+        // an explicit stack, like `deserialize_all`, so deep values can't overflow it
+        let mut stack: Vec<Rebuild<'a, 'static>> = Vec::new();
+        let mut next = self;
+
+        loop {
+            let done = match next {
+                Self::Null => Some(Json::Null),
+                Self::True => Some(Json::True),
+                Self::False => Some(Json::False),
+                Self::Number(val) => Some(Json::Number(Cow::Owned(val.into_owned()))),
+                Self::String(val) => Some(Json::String(Cow::Owned(val.into_owned()))),
+                Self::Array(arr) => {
+                    stack.push(Rebuild::array(arr));
+                    None
+                }
+                Self::Object(obj) => {
+                    stack.push(Rebuild::object(obj));
+                    None
+                }
+            };
+
+            match Rebuild::climb(&mut stack, done, |key| Cow::Owned(key.into_owned())) {
+                ControlFlow::Continue(child) => next = child,
+                ControlFlow::Break(root) => return root,
+            }
+        }
+    }
+
+    /// Parses nested JSON strings, with an explicit stack so deep values can't overflow it.
+    ///
+    /// `depth` containers enclose the value, and count towards `max_depth`.
+    pub(crate) fn deserialize_all(self, options: &FixOptions, depth: usize) -> Json<'a> {
+        let mut stack: Vec<Rebuild<'a, 'a>> = Vec::new();
+        let mut next = self;
+
+        loop {
+            // descend into `next` until a finished value is found
+            let done = match next {
+                Self::String(val) if val.trim_start().starts_with(['{', '[']) => {
+                    let nested = match val {
+                        Cow::Borrowed(val) => Cow::Borrowed(val.trim()),
+                        Cow::Owned(val) => Cow::Owned(val.trim().to_string()),
+                    };
+
+                    next = Parser::new(nested, options)
+                        .at_depth(depth + stack.len())
+                        .parse_and_fix();
+                    continue;
+                }
+
+                Self::Array(arr) => {
+                    stack.push(Rebuild::array(arr));
+                    None
+                }
+
+                Self::Object(obj) => {
+                    stack.push(Rebuild::object(obj));
+                    None
+                }
+
+                other => Some(other),
+            };
+
+            match Rebuild::climb(&mut stack, done, |key| key) {
+                ControlFlow::Continue(child) => next = child,
+                ControlFlow::Break(root) => return root,
+            }
+        }
+    }
+
+    pub(crate) fn stringify(&self, options: &FixOptions) -> String {
+        let mut result = String::new();
+        self.write_to(&mut result, options)
+            .expect("writing to a String never fails");
+        result
+    }
+
+    /// Prints the value in the output style of `options` straight into `w`, without
+    /// building intermediate strings, and with an explicit stack so deep values can't overflow it.
+    pub(crate) fn write_to<W: fmt::Write>(&self, w: &mut W, options: &FixOptions) -> fmt::Result {
+        let newline = options.line_ending.as_str();
+        let indent_width = options.indent.chars().count();
+        let mut stack: Vec<Print<'_, 'a>> = Vec::new();
+        let mut next = self;
+        // column where `next` starts, and the width taken after it on its line (a `,`)
+        let (mut column, mut after) = (0, 0);
+
+        loop {
+            let flat = options.minify
+                || stack.last().is_some_and(|frame| frame.flat)
+                || options.max_width.is_some_and(|max_width| {
+                    max_width
+                        .checked_sub(column + after)
+                        .and_then(|budget| next.flat_width(budget))
+                        .is_some()
+                });
+
+            match next {
+                Self::Null => w.write_str("null")?,
+                Self::True => w.write_str("true")?,
+                Self::False => w.write_str("false")?,
+
+                Self::Number(val) => w.write_str(val)?,
+                Self::String(val) => {
+                    w.write_char('"')?;
+                    write_escaped(w, val)?;
+                    w.write_char('"')?;
+                }
+
+                Self::Array(arr) if arr.is_empty() => w.write_str("[]")?,
+                Self::Array(arr) => {
+                    w.write_char('[')?;
+                    stack.push(Print {
+                        entries: Entries::Array(arr.iter()),
+                        first: true,
+                        flat,
+                    });
+                }
+
+                Self::Object(obj) if obj.is_empty() => w.write_str("{}")?,
+                Self::Object(obj) => {
+                    w.write_char('{')?;
+                    stack.push(Print {
+                        entries: Entries::Object(obj.iter()),
+                        first: true,
+                        flat,
+                    });
+                }
+            }
+
+            // close finished containers until one has an entry left to print
+            loop {
+                let depth = stack.len();
+                let Some(frame) = stack.last_mut() else {
+                    if options.trailing_newline {
+                        w.write_str(newline)?;
+                    }
+                    return Ok(());
+                };
+
+                let (entry, remaining) = match &mut frame.entries {
+                    Entries::Array(items) => (items.next().map(|val| (None, val)), items.len()),
+                    Entries::Object(entries) => (
+                        entries.next().map(|(key, val)| (Some(key), val)),
+                        entries.len(),
+                    ),
+                };
+
+                if let Some((key, val)) = entry {
+                    if !frame.first {
+                        w.write_char(',')?;
+                    }
+                    if frame.flat && !options.minify && !frame.first {
+                        w.write_char(' ')?;
+                    }
+                    if !frame.flat {
+                        w.write_str(newline)?;
+                        write_indent(w, &options.indent, depth)?;
+                        column = indent_width * depth;
+                        after = usize::from(remaining > 0);
+                    }
+                    frame.first = false;
+
+                    if let Some(key) = key {
+                        w.write_char('"')?;
+                        write_escaped(w, key)?;
+                        w.write_str(if options.minify { "\":" } else { "\": " })?;
+                        column += quoted_width(key) + 2;
+                    }
+
+                    next = val;
+                    break;
+                }
+
+                let frame = stack.pop().expect("checked above");
+                if !frame.flat {
+                    w.write_str(newline)?;
+                    write_indent(w, &options.indent, depth - 1)?;
+                }
+                w.write_char(match frame.entries {
+                    Entries::Array(_) => ']',
+                    Entries::Object(..) => '}',
+                })?;
+            }
+        }
+    }
+
+    /// Width of the value printed on a single line, or `None` if it exceeds `budget`.
+    fn flat_width(&self, budget: usize) -> Option<usize> {
+        let mut width = 0;
+        let mut stack = vec![self];
+
+        while let Some(json) = stack.pop() {
+            width += match json {
+                Self::Null | Self::True => 4,
+                Self::False => 5,
+                Self::Number(val) => val.len(),
+                Self::String(val) => quoted_width(val),
+
+                // brackets, then `, ` between elements
+                Self::Array(arr) => {
+                    let width = 2 * arr.len().max(1);
+                    if width <= budget {
+                        stack.extend(arr);
+                    }
+                    width
+                }
+
+                // brackets, then `, ` between entries and `"key": ` for each
+                Self::Object(obj) => {
+                    let mut width = 2 * obj.len().max(1);
+                    for key in obj.keys() {
+                        width += quoted_width(key) + 2;
+                        if width > budget {
+                            break;
+                        }
+                    }
+                    if width <= budget {
+                        stack.extend(obj.values());
+                    }
+                    width
+                }
+            };
+
+            if width > budget {
+                return None;
+            }
+        }
+
+        Some(width)
+    }
+}
+
+/// A container being rebuilt by [`Json::deserialize_all`] or [`Json::into_owned`], into a
+/// `Json<'b>`, with the children left to visit.
+enum Rebuild<'a, 'b> {
+    Array(Vec<Json<'b>>, std::vec::IntoIter<Json<'a>>),
+    Object {
+        obj: Map<'b>,
+        entries: crate::map::IntoIter<'a>,
+        /// Key of the child being visited.
+        key: Option<Cow<'b, str>>,
+    },
+}
+
+impl<'a, 'b> Rebuild<'a, 'b> {
+    fn array(arr: Vec<Json<'a>>) -> Self {
+        Self::Array(Vec::with_capacity(arr.len()), arr.into_iter())
+    }
+
+    fn object(obj: Map<'a>) -> Self {
+        Self::Object {
+            obj: Map::with_capacity(obj.len()),
+            entries: obj.into_iter(),
+            key: None,
+        }
+    }
+
+    /// Hands the finished value `done` to its container, and the containers it finishes to
+    /// theirs, until one has a child left to visit. Breaks with the root once it is finished.
+    fn climb(
+        stack: &mut Vec<Self>,
+        mut done: Option<Json<'b>>,
+        rekey: fn(Cow<'a, str>) -> Cow<'b, str>,
+    ) -> ControlFlow<Json<'b>, Json<'a>> {
+        loop {
+            let Some(frame) = stack.last_mut() else {
+                return ControlFlow::Break(
+                    done.expect("the root is finished once the stack is empty"),
+                );
+            };
+
+            let child = match frame {
+                Rebuild::Array(arr, rest) => {
+                    arr.extend(done.take());
+                    rest.next()
+                }
+                Rebuild::Object { obj, entries, key } => {
+                    if let (Some(key), Some(val)) = (key.take(), done.take()) {
+                        obj.insert(key, val);
+                    }
+                    entries.next().map(|(k, v)| {
+                        *key = Some(rekey(k));
+                        v
+                    })
+                }
+            };
+
+            if let Some(child) = child {
+                return ControlFlow::Continue(child);
+            }
+
+            done = Some(match stack.pop() {
+                Some(Rebuild::Array(arr, _)) => Json::Array(arr),
+                Some(Rebuild::Object { obj, .. }) => Json::Object(obj),
+                None => unreachable!(),
+            });
+        }
+    }
+}
+
+/// A container being printed by [`Json::write_to`].
+struct Print<'j, 'a> {
+    /// The entries left to print.
+    entries: Entries<'j, 'a>,
+    /// No entry was printed yet.
+    first: bool,
+    /// The container is printed on a single line.
+    flat: bool,
+}
+
+enum Entries<'j, 'a> {
+    Array(std::slice::Iter<'j, Json<'a>>),
+    Object(crate::map::Iter<'j, 'a>),
+}
+
+/// Converts numbers exactly, or fails with the reason they can't be.
+macro_rules! try_from_number {
+    ($($ty:ty => $convert:expr),* $(,)?) => {$(
+        impl TryFrom<&Json<'_>> for $ty {
+            type Error = NumberError;
+
+            fn try_from(json: &Json<'_>) -> Result<Self, Self::Error> {
+                let lex = json.as_number().ok_or(NumberError::NotANumber)?;
+                $convert(lex)
+            }
+        }
+    )*};
+}
+
+try_from_number! {
+    i128 => number::to_i128,
+    i64 => number::to_int,
+    u64 => number::to_int,
+    f64 => number::to_f64,
+    Decimal => number::to_decimal,
+}
+
+impl fmt::Display for Json<'_> {
+    /// Formats the value as pretty-printed JSON, the same way [`fix`](crate::fix) does.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f, &FixOptions::default())
+    }
+}
@@ -1,4 +1,29 @@
-use std::collections::HashMap;
+mod io;
+mod json;
+mod map;
+mod number;
+mod options;
+mod parser;
+mod report;
+#[cfg(feature = "serde")]
+mod serde;
+mod stream;
+mod strict;
+
+#[cfg(feature = "serde")]
+pub use self::serde::DeserializeError;
+pub use json::Json;
+pub use map::Map;
+pub use number::{Decimal, NumberError};
+pub use options::{
+    BareWords, Dialect, DuplicateKeys, FixOptions, LineEnding, NonFinite, NumberFormat, Undefined,
+};
+pub use report::{Diagnostic, RepairKind, Report};
+pub use stream::StreamParser;
+pub use strict::ParseError;
+
+use parser::Parser;
+use strict::StrictParser;
 
 /// Parses a JSON string and automatically corrects
 /// common issues (e.g., missing brackets, invalid numbers, incomplete literals).
@@ -7,475 +32,243 @@ use std::collections::HashMap;
 ///
 /// Returns a formatted, valid JSON string.
 pub fn fix(input: impl Into<String>) -> String {
-    let json = Parser::new(input).parse_and_fix();
-    json.deserialize_all().stringify(0)
+    fix_with(input, &FixOptions::default())
 }
 
-struct Parser {
-    chars: Vec<char>,
-    i: usize,
+/// Same as [`fix`], but with the behavior controlled by [`FixOptions`].
+pub fn fix_with(input: impl Into<String>, options: &FixOptions) -> String {
+    let input = input.into();
+    fix_value_borrowed_with(&input, options).stringify(options)
 }
 
-impl Parser {
-    pub fn new(input: impl Into<String>) -> Self {
-        Self {
-            chars: input.into().trim().chars().collect(),
-            i: 0,
-        }
-    }
-
-    fn peek(&mut self) -> Option<char> {
-        self.chars.get(self.i).copied()
-    }
-
-    fn next(&mut self) -> Option<char> {
-        let c = self.peek();
-        if c.is_some() {
-            self.i += 1;
-        }
-        c
-    }
-
-    fn skip_whitespace(&mut self) {
-        while let Some(c) = self.peek()
-            && c.is_whitespace()
-        {
-            self.next();
-        }
-    }
-
-    pub fn parse_and_fix(&mut self) -> Json {
-        self.parse_value()
-    }
-
-    fn parse_value(&mut self) -> Json {
-        self.skip_whitespace();
-
-        if let Some(c) = self.peek() {
-            match c {
-                'n' | 'N' | 't' | 'T' | 'f' | 'F' => self.parse_static(),
-
-                val if val.is_ascii_digit() || val == '-' || val == '.' => self.parse_number(),
-
-                '"' => self.parse_string(),
-
-                '[' => self.parse_array(),
-
-                '{' => self.parse_object(),
-
-                _ => {
-                    self.next();
-                    Json::Null
-                }
-            }
-        } else {
-            Json::Null
-        }
-    }
-
-    fn parse_static(&mut self) -> Json {
-        match self.next().unwrap().to_ascii_lowercase() {
-            'n' => Json::Null,
-            't' => Json::True,
-            _ => Json::False,
-        }
-    }
-
-    fn parse_number(&mut self) -> Json {
-        let mut lex = String::new();
-
-        if let Some('-') = self.peek() {
-            lex.push('-');
-            self.next();
-
-            while let Some('-') = self.peek() {
-                self.next();
-            }
-        }
-
-        if let Some('.') = self.peek() {
-            lex.push('0');
-        }
-
-        // leading 0
-        if let Some('0') = self.peek() {
-            lex.push('0');
-            self.next();
-
-            while let Some(c) = self.peek() {
-                if c == '0' {
-                    self.next();
-                } else {
-                    if c.is_ascii_digit() {
-                        lex.pop();
-                    }
-                    break;
-                }
-            }
-        }
-
-        // integer
-        while let Some(c) = self.peek()
-            && c.is_ascii_digit()
-        {
-            lex.push(c);
-            self.next();
-        }
-
-        // float
-        if let Some('.') = self.peek() {
-            lex.push('.');
-            self.next();
-
-            let mut count = 0;
-            while let Some(c) = self.peek()
-                && c.is_ascii_digit()
-            {
-                lex.push(c);
-                self.next();
-                count += 1;
-            }
-
-            if count == 0 {
-                lex.push('0');
-            }
-        }
-
-        // expoent
-        if let Some(c) = self.peek()
-            && (c == 'e' || c == 'E')
-        {
-            lex.push(c);
-            self.next();
-
-            if let Some(sign) = self.peek()
-                && (sign == '-' || sign == '+')
-            {
-                lex.push(sign);
-                self.next();
-            }
-
-            let mut count = 0;
-            while let Some(c) = self.peek()
-                && c.is_ascii_digit()
-            {
-                lex.push(c);
-                self.next();
-                count += 1;
-            }
-
-            if count == 0 {
-                if lex.ends_with('-') || lex.ends_with('+') {
-                    lex.pop();
-                }
-                lex.pop();
-            }
-        }
-
-        if lex == "-" {
-            lex.push('0');
-        }
-
-        Json::Number(lex)
-    }
-
-    fn parse_string(&mut self) -> Json {
-        let mut lex = String::new();
-
-        self.next();
-        while let Some(c) = self.next() {
-            match c {
-                '"' => {
-                    break;
-                }
-
-                '\\' => {
-                    if let Some(esc) = self.next() {
-                        match esc {
-                            '"' => lex.push('"'),
-                            '\\' => lex.push('\\'),
-                            '/' => lex.push('/'),
-                            'b' => lex.push('\u{0008}'),
-                            'f' => lex.push('\u{000C}'),
-                            'n' => lex.push('\n'),
-                            'r' => lex.push('\r'),
-                            't' => lex.push('\t'),
-
-                            'u' => {
-                                if let Some(c) = self.parse_unicode_escape() {
-                                    lex.push(c);
-                                }
-                            }
-
-                            ch => {
-                                lex.push(ch);
-                            }
-                        }
-                    }
-                }
-
-                _ => {
-                    lex.push(c);
-                }
-            }
-        }
-
-        Json::String(lex)
-    }
-
-    fn parse_unicode_escape(&mut self) -> Option<char> {
-        let mut hex = String::new();
-        for _ in 0..4 {
-            if let Some(c) = self.next() {
-                hex.push(c);
-            } else {
-                return None;
-            }
-        }
-
-        let code = u32::from_str_radix(&hex, 16).ok()?;
-
-        if let Some(ch) = char::from_u32(code) {
-            Some(ch)
-        } else if (0xD800..=0xDBFF).contains(&code) {
-            if let (Some('\\'), Some('u')) = (self.next(), self.next()) {
-                let mut low_hex = String::new();
-                for _ in 0..4 {
-                    if let Some(c) = self.next() {
-                        low_hex.push(c);
-                    } else {
-                        return None;
-                    }
-                }
-
-                let low_code = u32::from_str_radix(&low_hex, 16).ok()?;
-
-                if (0xDC00..=0xDFFF).contains(&low_code) {
-                    let full_code = 0x10000 + ((code - 0xD800) << 10) + (low_code - 0xDC00);
-
-                    char::from_u32(full_code)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    }
-
-    fn parse_array(&mut self) -> Json {
-        let mut arr = Vec::new();
-
-        self.next();
-
-        loop {
-            self.skip_whitespace();
-
-            match self.peek() {
-                Some(']') => {
-                    self.next();
-                    break;
-                }
-
-                Some(',') => {
-                    self.next();
-                    continue;
-                }
-
-                Some(_) => {
-                    arr.push(self.parse_value());
-
-                    self.skip_whitespace();
-
-                    while let Some(c) = self.peek() {
-                        match c {
-                            ']' | ',' => {
-                                break;
-                            }
-
-                            _ => {
-                                self.next();
-                            }
-                        }
-                    }
-                }
-
-                None => {
-                    break;
-                }
-            }
-        }
-
-        Json::Array(arr)
-    }
-
-    fn parse_object(&mut self) -> Json {
-        let mut obj = HashMap::new();
-        let mut order = Vec::new();
-
-        self.next();
-
-        loop {
-            self.skip_whitespace();
-
-            match self.peek() {
-                Some('}') => {
-                    self.next();
-                    break;
-                }
-
-                Some('"') => {
-                    let key = match self.parse_string() {
-                        Json::String(s) => s,
-                        _ => unreachable!(),
-                    };
-
-                    self.skip_whitespace();
-
-                    if self.peek() == Some(':') {
-                        self.next();
-                    }
+/// Same as [`fix`], but reads the input from `reader` in chunks instead of a `String`.
+///
+/// Returns an [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData) error if the
+/// input is not valid UTF-8 (a character cut off at the very end is dropped instead).
+pub fn fix_reader<R: std::io::Read>(reader: R) -> std::io::Result<String> {
+    fix_reader_with(reader, &FixOptions::default())
+}
 
-                    if obj.contains_key(&key)
-                        && let Some(pos) = order.iter().position(|k| k == &key)
-                    {
-                        order.remove(pos);
-                    }
+/// Same as [`fix_reader`], but with the behavior controlled by [`FixOptions`].
+pub fn fix_reader_with<R: std::io::Read>(
+    reader: R,
+    options: &FixOptions,
+) -> std::io::Result<String> {
+    Ok(io::read_value(reader, options)?.stringify(options))
+}
 
-                    obj.insert(key.clone(), self.parse_value());
-                    order.push(key);
-                }
+/// Same as [`fix`], but writes the output to `writer` as it is printed instead of
+/// returning it as a `String`.
+///
+/// The output is buffered internally and flushed before returning.
+///
+/// ```
+/// let mut out = Vec::new();
+/// fjson_core::fix_to_writer("[1, 2", &mut out).unwrap();
+///
+/// assert_eq!(out, b"[\n   1,\n   2\n]");
+/// ```
+pub fn fix_to_writer<W: std::io::Write>(
+    input: impl Into<String>,
+    writer: W,
+) -> std::io::Result<()> {
+    fix_to_writer_with(input, writer, &FixOptions::default())
+}
 
-                None => {
-                    break;
-                }
+/// Same as [`fix_to_writer`], but with the behavior controlled by [`FixOptions`].
+pub fn fix_to_writer_with<W: std::io::Write>(
+    input: impl Into<String>,
+    writer: W,
+    options: &FixOptions,
+) -> std::io::Result<()> {
+    let input = input.into();
+    io::write_value(&fix_value_borrowed_with(&input, options), writer, options)
+}
 
-                _ => {
-                    self.next();
-                }
-            }
-        }
+/// Same as [`fix_to_writer`], but for a [`fmt::Write`](std::fmt::Write) such as a
+/// `String` or a [`Formatter`](std::fmt::Formatter).
+pub fn fix_to_fmt<W: std::fmt::Write>(input: impl Into<String>, writer: W) -> std::fmt::Result {
+    fix_to_fmt_with(input, writer, &FixOptions::default())
+}
 
-        Json::Object((obj, order))
-    }
+/// Same as [`fix_to_fmt`], but with the behavior controlled by [`FixOptions`].
+pub fn fix_to_fmt_with<W: std::fmt::Write>(
+    input: impl Into<String>,
+    mut writer: W,
+    options: &FixOptions,
+) -> std::fmt::Result {
+    let input = input.into();
+    fix_value_borrowed_with(&input, options).write_to(&mut writer, options)
 }
 
-fn escape_string(s: &str) -> String {
-    let mut result = String::with_capacity(s.len());
-    for c in s.chars() {
-        match c {
-            '"' => result.push_str("\\\""),
-            '\\' => result.push_str("\\\\"),
-            '\n' => result.push_str("\\n"),
-            '\r' => result.push_str("\\r"),
-            '\t' => result.push_str("\\t"),
-            '\u{0008}' => result.push_str("\\b"),
-            '\u{000C}' => result.push_str("\\f"),
-            ch if ch < ' ' => {
-                result.push_str(&format!("\\u{:04x}", ch as u32));
-            }
-            ch => result.push(ch),
-        }
-    }
-    result
+/// Combines [`fix_reader`] and [`fix_to_writer`]: neither the raw input nor the
+/// printed output is held in memory in full.
+pub fn fix_reader_to_writer<R: std::io::Read, W: std::io::Write>(
+    reader: R,
+    writer: W,
+) -> std::io::Result<()> {
+    fix_reader_to_writer_with(reader, writer, &FixOptions::default())
 }
 
-enum Json {
-    Null,
-    True,
-    False,
-    Number(String),
-    String(String),
-    Array(Vec<Json>),
-    Object((HashMap<String, Json>, Vec<String>)),
+/// Same as [`fix_reader_to_writer`], but with the behavior controlled by [`FixOptions`].
+pub fn fix_reader_to_writer_with<R: std::io::Read, W: std::io::Write>(
+    reader: R,
+    writer: W,
+    options: &FixOptions,
+) -> std::io::Result<()> {
+    let json = io::read_value(reader, options)?;
+    io::write_value(&json, writer, options)
 }
 
-impl Json {
-    pub fn deserialize_all(self) -> Json {
-        match self {
-            Self::String(val) => {
-                let trimmed = val.trim();
+/// Same as [`fix`], but returns the repaired [`Json`] tree instead of a string.
+pub fn fix_value(input: impl Into<String>) -> Json<'static> {
+    fix_value_with(input, &FixOptions::default())
+}
 
-                if trimmed.starts_with('{') || trimmed.starts_with('[') {
-                    Parser::new(trimmed).parse_and_fix().deserialize_all()
-                } else {
-                    Json::String(val)
-                }
-            }
+/// Same as [`fix_value`], but with the behavior controlled by [`FixOptions`].
+pub fn fix_value_with(input: impl Into<String>, options: &FixOptions) -> Json<'static> {
+    let json = Parser::new(input.into(), options).parse_and_fix();
+    deserialize(json, options)
+}
 
-            Json::Array(arr) => Json::Array(arr.into_iter().map(|v| v.deserialize_all()).collect()),
+/// Same as [`fix_value`], but strings, keys and numbers that need no unescaping
+/// or repair borrow from `input` instead of being copied.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use fjson_core::Json;
+///
+/// let input = String::from(r#"{"name": "bob", "bio": "line\nbreak", "age": 42"#);
+/// let json = fjson_core::fix_value_borrowed(&input);
+///
+/// assert!(matches!(json.get("name"), Some(Json::String(Cow::Borrowed("bob")))));
+/// assert!(matches!(json.get("bio"), Some(Json::String(Cow::Owned(_)))));
+/// assert!(matches!(json.get("age"), Some(Json::Number(Cow::Borrowed("42")))));
+/// ```
+pub fn fix_value_borrowed(input: &str) -> Json<'_> {
+    fix_value_borrowed_with(input, &FixOptions::default())
+}
 
-            Json::Object((obj, order)) => Json::Object((
-                obj.into_iter()
-                    .map(|(k, v)| (k, v.deserialize_all()))
-                    .collect(),
-                order,
-            )),
+/// Same as [`fix_value_borrowed`], but with the behavior controlled by [`FixOptions`].
+pub fn fix_value_borrowed_with<'a>(input: &'a str, options: &FixOptions) -> Json<'a> {
+    let json = Parser::new(input, options).parse_and_fix();
+    deserialize(json, options)
+}
 
-            other => other,
-        }
+/// Same as [`fix_with`], but also lists every repair applied to the input.
+///
+/// ```
+/// use fjson_core::{FixOptions, RepairKind};
+///
+/// let report = fjson_core::fix_with_report("[1, 2", &FixOptions::default());
+///
+/// assert_eq!(report.output, "[\n   1,\n   2\n]");
+/// assert_eq!(report.diagnostics[0].kind, RepairKind::UnclosedArray);
+/// assert_eq!((report.diagnostics[0].line, report.diagnostics[0].column), (1, 1));
+/// ```
+pub fn fix_with_report(input: impl Into<String>, options: &FixOptions) -> Report {
+    let input = input.into();
+
+    let mut parser = Parser::new(input.as_str(), options);
+    let json = parser.parse_and_fix();
+
+    let mut diagnostics = parser.into_diagnostics();
+    report::locate(&input, &mut diagnostics);
+
+    Report {
+        output: deserialize(json, options).stringify(options),
+        diagnostics,
     }
+}
 
-    pub fn stringify(&self, tabs: usize) -> String {
-        const TAB: &str = "   ";
-
-        match self {
-            Self::Null => "null".to_string(),
-            Self::True => "true".to_string(),
-            Self::False => "false".to_string(),
-
-            Self::Number(val) => val.clone(),
-            Self::String(val) => format!("\"{}\"", escape_string(val)),
-
-            Self::Array(arr) => {
-                if arr.is_empty() {
-                    return "[]".to_string();
-                }
-
-                let mut result = String::from("[\n");
-                let tab_str = TAB.repeat(tabs + 1);
-
-                for val in arr {
-                    result.push_str(&format!("{}{},\n", tab_str, val.stringify(tabs + 1)));
-                }
-
-                result.truncate(result.len() - 2);
-
-                result.push('\n');
-                result.push_str(&TAB.repeat(tabs));
-                result.push(']');
-                result
-            }
+/// Parses `input` as strict RFC 8259 JSON, without repairing anything.
+///
+/// Returns the first error with its position, so [`fix`] can be offered as the remediation.
+/// Nested JSON strings are not deserialized. Strings without escapes, keys and
+/// numbers borrow from `input`.
+///
+/// ```
+/// let err = fjson_core::parse_strict("{\"a\": [1, 2,]}").unwrap_err();
+///
+/// assert_eq!((err.line, err.column, err.offset), (1, 13, 12));
+/// assert_eq!(err.to_string(), "line 1, column 13: expected a JSON value, found ']'");
+/// ```
+pub fn parse_strict(input: &str) -> Result<Json<'_>, ParseError> {
+    parse_strict_with(input, &FixOptions::default())
+}
 
-            Self::Object((obj, order)) => {
-                if obj.is_empty() {
-                    return "{}".to_string();
-                }
+/// Same as [`parse_strict`], with the [`duplicate_keys`](FixOptions::duplicate_keys)
+/// policy of `options`. [`DuplicateKeys::Error`] rejects repeated keys.
+///
+/// The other options only affect repairs and printing, so they are ignored.
+///
+/// ```
+/// use fjson_core::{DuplicateKeys, FixOptions};
+///
+/// let options = FixOptions::new().duplicate_keys(DuplicateKeys::Error);
+/// let err = fjson_core::parse_strict_with(r#"{"a": 1, "a": 2}"#, &options).unwrap_err();
+///
+/// assert_eq!(err.to_string(), "line 1, column 10: expected a unique key, found duplicate key \"a\"");
+/// ```
+pub fn parse_strict_with<'a>(input: &'a str, options: &FixOptions) -> Result<Json<'a>, ParseError> {
+    StrictParser::new(input, options.duplicate_keys).parse()
+}
 
-                let mut result = String::from("{\n");
-                let tab_str = TAB.repeat(tabs + 1);
+/// Repairs `input` and deserializes it straight into `T`.
+///
+/// On failure, the error lists every repair applied to the input.
+/// Requires the `serde` feature.
+///
+/// ```
+/// #[derive(Debug, serde::Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// let user: User = fjson_core::fix_to(r#"{"name": "alice", "age": 30"#).unwrap();
+/// assert_eq!((user.name.as_str(), user.age), ("alice", 30));
+///
+/// let err = fjson_core::fix_to::<User>(r#"{"name": "bob""#).unwrap_err();
+/// assert_eq!(err.message, "missing field `age`");
+/// assert_eq!(err.diagnostics[0].kind, fjson_core::RepairKind::UnclosedObject);
+/// ```
+#[cfg(feature = "serde")]
+pub fn fix_to<T: ::serde::de::DeserializeOwned>(
+    input: impl Into<String>,
+) -> Result<T, DeserializeError> {
+    fix_to_with(input, &FixOptions::default())
+}
 
-                for key in order {
-                    if let Some(val) = obj.get(key) {
-                        result.push_str(&format!(
-                            "{}\"{}\": {},\n",
-                            tab_str,
-                            escape_string(key),
-                            val.stringify(tabs + 1)
-                        ));
-                    }
-                }
+/// Same as [`fix_to`], but with the behavior controlled by [`FixOptions`].
+#[cfg(feature = "serde")]
+pub fn fix_to_with<T: ::serde::de::DeserializeOwned>(
+    input: impl Into<String>,
+    options: &FixOptions,
+) -> Result<T, DeserializeError> {
+    let input = input.into();
+
+    let mut parser = Parser::new(input.as_str(), options);
+    let json = deserialize(parser.parse_and_fix(), options);
+
+    T::deserialize(json).map_err(|mut err| {
+        let mut diagnostics = parser.into_diagnostics();
+        report::locate(&input, &mut diagnostics);
+        err.diagnostics = diagnostics;
+        err
+    })
+}
 
-                result.truncate(result.len() - 2);
+pub(crate) fn deserialize<'a>(json: Json<'a>, options: &FixOptions) -> Json<'a> {
+    deserialize_at(json, options, 0)
+}
 
-                result.push('\n');
-                result.push_str(&TAB.repeat(tabs));
-                result.push('}');
-                result
-            }
-        }
+/// Same as [`deserialize`], for a value enclosed in `depth` containers.
+pub(crate) fn deserialize_at<'a>(json: Json<'a>, options: &FixOptions, depth: usize) -> Json<'a> {
+    if options.deserialize_nested {
+        json.deserialize_all(options, depth)
+    } else {
+        json
     }
 }
 
@@ -0,0 +1,508 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::iter::FusedIterator;
+use std::{mem, slice, vec};
+
+use crate::{DuplicateKeys, Json};
+
+/// Maps up to this many entries are searched linearly instead of through the index.
+const LINEAR_MAX: usize = 8;
+/// An unused slot of the index.
+const EMPTY: usize = usize::MAX;
+
+/// The entries of a JSON object, in insertion order.
+///
+/// Lookups, inserts and replacements are O(1) (amortized), whatever the number of keys.
+/// Keys are stored once, and borrow from the input like [`Json`] strings do.
+///
+/// ```
+/// use fjson_core::Json;
+///
+/// let json = fjson_core::fix_value(r#"{"b": 1, "a": 2"#);
+/// let map = json.as_object().unwrap();
+///
+/// assert_eq!(map.keys().collect::<Vec<_>>(), ["b", "a"]);
+/// assert_eq!(map.get("a").and_then(Json::as_number), Some("2"));
+/// ```
+#[derive(Clone, Default)]
+pub struct Map<'a> {
+    /// Entries in insertion order; `None` where an entry was removed or moved to the end.
+    entries: Vec<Option<(Cow<'a, str>, Json<'a>)>>,
+    len: usize,
+    /// Open-addressing table of positions in `entries`, left empty while the map is small.
+    index: Vec<usize>,
+    hasher: RandomState,
+}
+
+impl<'a> Map<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.find(key).is_ok()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json<'a>> {
+        let (pos, _) = self.find(key).ok()?;
+        self.entries[pos].as_ref().map(|(_, value)| value)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Json<'a>> {
+        let (pos, _) = self.find(key).ok()?;
+        self.entries[pos].as_mut().map(|(_, value)| value)
+    }
+
+    /// Inserts `value` under `key` and returns the previous value.
+    ///
+    /// A replaced key keeps its position.
+    pub fn insert(&mut self, key: impl Into<Cow<'a, str>>, value: Json<'a>) -> Option<Json<'a>> {
+        let key = key.into();
+        match self.find(&key) {
+            Ok((pos, _)) => {
+                let (_, old) = self.entries[pos].as_mut().expect("indexed entries exist");
+                Some(mem::replace(old, value))
+            }
+            Err(slot) => {
+                self.push(key, value, slot);
+                None
+            }
+        }
+    }
+
+    /// Same as [`insert`](Self::insert), but a replaced key moves to the last position.
+    pub fn insert_last(
+        &mut self,
+        key: impl Into<Cow<'a, str>>,
+        value: Json<'a>,
+    ) -> Option<Json<'a>> {
+        let key = key.into();
+        match self.find(&key) {
+            Ok((pos, slot)) => {
+                let (key, old) = self.entries[pos].take().expect("indexed entries exist");
+                self.entries.push(Some((key, value)));
+                if let Some(slot) = slot {
+                    self.index[slot] = self.entries.len() - 1;
+                }
+                self.compact();
+                Some(old)
+            }
+            Err(slot) => {
+                self.push(key, value, slot);
+                None
+            }
+        }
+    }
+
+    /// Removes `key` and returns its value. The other entries keep their order.
+    pub fn remove(&mut self, key: &str) -> Option<Json<'a>> {
+        let (pos, slot) = self.find(key).ok()?;
+        let (_, value) = self.entries[pos].take().expect("indexed entries exist");
+        self.len -= 1;
+
+        if let Some(slot) = slot {
+            self.unindex(slot);
+        }
+        self.compact();
+        Some(value)
+    }
+
+    /// Entries in insertion order.
+    pub fn iter(&self) -> Iter<'_, 'a> {
+        Iter {
+            entries: self.entries.iter(),
+            len: self.len,
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Json<'a>> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Json<'a>> {
+        self.entries.iter_mut().flatten().map(|(_, value)| value)
+    }
+
+    /// Removes the last entry, which must not have been moved or removed.
+    pub(crate) fn pop(&mut self) -> Option<(Cow<'a, str>, Json<'a>)> {
+        let pos = self.entries.len().checked_sub(1)?;
+        let (_, slot) = self.find(self.key_at(pos)).expect("the last entry exists");
+        if let Some(slot) = slot {
+            self.unindex(slot);
+        }
+        self.len -= 1;
+        self.entries.pop().flatten()
+    }
+
+    /// Moves the entry of `key`, which must exist, last with `value`, like
+    /// [`insert_last`](Self::insert_last) without compacting. Returns its position and old
+    /// value, for [`unmove_last`](Self::unmove_last).
+    pub(crate) fn move_last(&mut self, key: Cow<'a, str>, value: Json<'a>) -> (usize, Json<'a>) {
+        let (pos, slot) = self.find(&key).expect("the key exists");
+        let (key, old) = self.entries[pos].take().expect("indexed entries exist");
+        self.entries.push(Some((key, value)));
+        if let Some(slot) = slot {
+            self.index[slot] = self.entries.len() - 1;
+        }
+        (pos, old)
+    }
+
+    /// Undoes [`move_last`](Self::move_last), returning the value it moved.
+    pub(crate) fn unmove_last(&mut self, pos: usize, old: Json<'a>) -> Json<'a> {
+        let last = self.entries.len() - 1;
+        let (_, slot) = self.find(self.key_at(last)).expect("the last entry exists");
+        let (key, value) = self
+            .entries
+            .pop()
+            .flatten()
+            .expect("moved entries are last");
+        if let Some(slot) = slot {
+            self.index[slot] = pos;
+        }
+        self.entries[pos] = Some((key, old));
+        value
+    }
+
+    /// Number of entry positions, including the gaps left by moved and removed entries.
+    pub(crate) fn positions(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The entry at position `pos`, unless it was moved or removed.
+    pub(crate) fn entry_at(&self, pos: usize) -> Option<(&Cow<'a, str>, &Json<'a>)> {
+        self.entries[pos].as_ref().map(|(key, value)| (key, value))
+    }
+
+    /// Entries from position `start` on.
+    pub(crate) fn iter_from(
+        &self,
+        start: usize,
+    ) -> impl Iterator<Item = (&Cow<'a, str>, &Json<'a>)> {
+        self.entries[start..]
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key, value))
+    }
+
+    /// Finds the position of `key` in `entries` and its slot in the index, if indexed.
+    ///
+    /// If it is absent, returns the index slot where it would go.
+    fn find(&self, key: &str) -> Result<(usize, Option<usize>), Option<usize>> {
+        if self.index.is_empty() {
+            return self
+                .entries
+                .iter()
+                .position(|entry| entry.as_ref().is_some_and(|(k, _)| k == key))
+                .map(|pos| (pos, None))
+                .ok_or(None);
+        }
+
+        let mask = self.index.len() - 1;
+        let mut slot = self.hasher.hash_one(key) as usize & mask;
+        loop {
+            match self.index[slot] {
+                EMPTY => return Err(Some(slot)),
+                pos if self.key_at(pos) == key => return Ok((pos, Some(slot))),
+                _ => slot = (slot + 1) & mask,
+            }
+        }
+    }
+
+    fn key_at(&self, pos: usize) -> &str {
+        self.entries[pos]
+            .as_ref()
+            .map(|(key, _)| key.as_ref())
+            .expect("indexed entries exist")
+    }
+
+    fn push(&mut self, key: Cow<'a, str>, value: Json<'a>, slot: Option<usize>) {
+        self.entries.push(Some((key, value)));
+        self.len += 1;
+
+        // keep the index at most half full
+        if self.entries.len() * 2 > self.index.len() && self.entries.len() > LINEAR_MAX {
+            self.reindex();
+        } else if let Some(slot) = slot {
+            self.index[slot] = self.entries.len() - 1;
+        }
+    }
+
+    /// Drops the gaps left by moved and removed entries once they outnumber the entries.
+    fn compact(&mut self) {
+        if self.entries.len() > 2 * self.len + LINEAR_MAX {
+            self.entries.retain(Option::is_some);
+            self.reindex();
+        }
+    }
+
+    fn reindex(&mut self) {
+        self.index.clear();
+        if self.entries.len() <= LINEAR_MAX {
+            return;
+        }
+
+        let size = (self.entries.len() * 2).next_power_of_two();
+        self.index.resize(size, EMPTY);
+        let mask = size - 1;
+
+        for (pos, entry) in self.entries.iter().enumerate() {
+            let Some((key, _)) = entry else {
+                continue;
+            };
+            let mut slot = self.hasher.hash_one(key.as_ref()) as usize & mask;
+            while self.index[slot] != EMPTY {
+                slot = (slot + 1) & mask;
+            }
+            self.index[slot] = pos;
+        }
+    }
+
+    /// Empties `slot`, shifting back the entries that probed past it.
+    fn unindex(&mut self, mut hole: usize) {
+        let mask = self.index.len() - 1;
+        self.index[hole] = EMPTY;
+
+        let mut slot = (hole + 1) & mask;
+        while self.index[slot] != EMPTY {
+            let home = self.hasher.hash_one(self.key_at(self.index[slot])) as usize & mask;
+            // move the entry into the hole unless its home slot lies between the hole and it
+            if (slot.wrapping_sub(home) & mask) >= (slot.wrapping_sub(hole) & mask) {
+                self.index[hole] = self.index[slot];
+                self.index[slot] = EMPTY;
+                hole = slot;
+            }
+            slot = (slot + 1) & mask;
+        }
+    }
+}
+
+/// Collects the entries of a parsed object, applying a [`DuplicateKeys`] policy.
+#[derive(Clone, Default)]
+pub(crate) struct MapBuilder<'a> {
+    map: Map<'a>,
+    /// Occurrences of the keys seen more than once.
+    repeats: HashMap<Cow<'a, str>, usize>,
+    /// Changes to entries other than appending them, for copies of the map to follow.
+    changes: Vec<Change<'a>>,
+    /// Times the entries were compacted, which moves them to other positions.
+    compactions: usize,
+}
+
+/// A change [`MapBuilder::push`] made to an existing entry.
+#[derive(Clone)]
+pub(crate) enum Change<'a> {
+    /// The entry of the key was removed, before the key was added again.
+    Removed(Cow<'a, str>),
+    /// The value at this position changed.
+    Updated(usize),
+}
+
+impl<'a> MapBuilder<'a> {
+    /// Adds an entry. If its key is a duplicate, describes what was done with the value.
+    pub fn push(
+        &mut self,
+        key: Cow<'a, str>,
+        value: Json<'a>,
+        policy: DuplicateKeys,
+    ) -> Option<String> {
+        let pos = match self.map.find(&key) {
+            Ok((pos, _)) => pos,
+            Err(slot) => {
+                self.map.push(key, value, slot);
+                return None;
+            }
+        };
+
+        let action = match policy {
+            DuplicateKeys::LastWins | DuplicateKeys::Error => {
+                let message = format!("duplicate key {key:?}: kept the last value");
+                let positions = self.map.positions();
+                self.changes.push(Change::Removed(key.clone()));
+                self.map.insert_last(key, value);
+                if self.map.positions() != positions + 1 {
+                    self.compactions += 1;
+                }
+                return Some(message);
+            }
+            DuplicateKeys::FirstWins => "kept the first value",
+            DuplicateKeys::KeepAll => {
+                let count = self.repeats.entry(key.clone()).or_insert(1);
+                *count += 1;
+                let (_, slot) = self.map.entries[pos].as_mut().expect("found above");
+                if *count == 2 {
+                    *slot = Json::Array(vec![mem::replace(slot, Json::Null)]);
+                }
+                if let Json::Array(values) = slot {
+                    values.push(value);
+                }
+                self.changes.push(Change::Updated(pos));
+                return Some(format!("duplicate key {key:?}: kept all {count} values"));
+            }
+            DuplicateKeys::Rename => {
+                let count = self.repeats.entry(key.clone()).or_insert(1);
+                let renamed = loop {
+                    *count += 1;
+                    let renamed = format!("{key}#{count}");
+                    if !self.map.contains_key(&renamed) {
+                        break renamed;
+                    }
+                };
+                let message = format!("duplicate key {key:?}: renamed it to {renamed:?}");
+                self.map.insert(renamed, value);
+                return Some(message);
+            }
+        };
+        Some(format!("duplicate key {key:?}: {action}"))
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn map(&self) -> &Map<'a> {
+        &self.map
+    }
+
+    pub fn changes(&self) -> &[Change<'a>] {
+        &self.changes
+    }
+
+    pub fn compactions(&self) -> usize {
+        self.compactions
+    }
+
+    /// Occurrences of `key` so far, if it was seen more than once (`1` otherwise).
+    pub fn repeats(&self, key: &str) -> usize {
+        self.repeats.get(key).copied().unwrap_or(1)
+    }
+
+    pub fn finish(self) -> Map<'a> {
+        self.map
+    }
+}
+
+impl PartialEq for Map<'_> {
+    /// Maps are equal if they have the same entries in the same order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl fmt::Debug for Map<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K: Into<Cow<'a, str>>> FromIterator<(K, Json<'a>)> for Map<'a> {
+    fn from_iter<I: IntoIterator<Item = (K, Json<'a>)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut map = Self::with_capacity(iter.size_hint().0);
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<'a, K: Into<Cow<'a, str>>> Extend<(K, Json<'a>)> for Map<'a> {
+    fn extend<I: IntoIterator<Item = (K, Json<'a>)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+/// Iterator over the entries of a [`Map`], in insertion order.
+#[derive(Clone)]
+pub struct Iter<'m, 'a> {
+    entries: slice::Iter<'m, Option<(Cow<'a, str>, Json<'a>)>>,
+    len: usize,
+}
+
+impl<'m, 'a> Iterator for Iter<'m, 'a> {
+    type Item = (&'m str, &'m Json<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.entries.find_map(Option::as_ref)?;
+        self.len -= 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_, '_> {}
+impl FusedIterator for Iter<'_, '_> {}
+
+impl<'m, 'a> IntoIterator for &'m Map<'a> {
+    type Item = (&'m str, &'m Json<'a>);
+    type IntoIter = Iter<'m, 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Owning iterator over the entries of a [`Map`], in insertion order.
+pub struct IntoIter<'a> {
+    entries: vec::IntoIter<Option<(Cow<'a, str>, Json<'a>)>>,
+    len: usize,
+}
+
+impl<'a> Iterator for IntoIter<'a> {
+    type Item = (Cow<'a, str>, Json<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.entries.find_map(|entry| entry)?;
+        self.len -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl ExactSizeIterator for IntoIter<'_> {}
+impl FusedIterator for IntoIter<'_> {}
+
+impl<'a> IntoIterator for Map<'a> {
+    type Item = (Cow<'a, str>, Json<'a>);
+    type IntoIter = IntoIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            entries: self.entries.into_iter(),
+            len: self.len,
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+/// Largest integer below which every integer fits exactly in a double (`Number.MAX_SAFE_INTEGER`).
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Returns `true` if the valid number `lex` is an integer beyond ±(2^53 - 1), which a double
+/// (e.g. a JavaScript number) can't hold exactly.
+pub(crate) fn is_unsafe_integer(lex: &str) -> bool {
+    let digits = lex.strip_prefix('-').unwrap_or(lex);
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+
+    let digits = digits.trim_start_matches('0');
+    digits.len() > 16 || digits.parse::<u64>().is_ok_and(|n| n > MAX_SAFE_INTEGER)
+}
+
+/// Formats the valid number `lex` the way JavaScript prints the closest double: the shortest
+/// digits that round-trip, with an exponent only below `1e-6` or from `1e21` on (RFC 8785).
+///
+/// Returns `None` if the number overflows a double.
+pub(crate) fn canonical(lex: &str) -> Option<String> {
+    let value: f64 = lex.parse().ok()?;
+    if !value.is_finite() {
+        return None;
+    }
+    if value == 0.0 {
+        return Some(String::from("0"));
+    }
+
+    // shortest round-trip digits, e.g. `1.25e-7`
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific.split_once('e')?;
+    let digits = mantissa.replace('.', "");
+    // the value is `0.digits × 10^point`
+    let point = exponent.parse::<i32>().ok()? + 1;
+    let len = digits.len() as i32;
+
+    let mut result = String::with_capacity(digits.len() + 8);
+    if value < 0.0 {
+        result.push('-');
+    }
+
+    match point {
+        // integer: `1000`
+        p if len <= p && p <= 21 => {
+            result.push_str(&digits);
+            result.extend(std::iter::repeat_n('0', (p - len) as usize));
+        }
+        // point inside the digits: `12.5`
+        p if 0 < p && p <= 21 => {
+            result.push_str(&digits[..p as usize]);
+            result.push('.');
+            result.push_str(&digits[p as usize..]);
+        }
+        // small fraction: `0.00125`
+        p if -6 < p && p <= 0 => {
+            result.push_str("0.");
+            result.extend(std::iter::repeat_n('0', -p as usize));
+            result.push_str(&digits);
+        }
+        // exponent: `1.25e-7`, `1e+21`
+        p => {
+            result.push_str(&digits[..1]);
+            if len > 1 {
+                result.push('.');
+                result.push_str(&digits[1..]);
+            }
+            result.push_str(if p > 0 { "e+" } else { "e-" });
+            result.push_str(&(p - 1).abs().to_string());
+        }
+    }
+
+    Some(result)
+}
+
+/// Returns `true` if `raw` is a valid JSON5 decimal number, including `+1`, `.5` and `5.`.
+pub(crate) fn is_js_decimal(raw: &str) -> bool {
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+
+    let unsigned = raw.strip_prefix(['+', '-']).unwrap_or(raw);
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(i) => (&unsigned[..i], Some(&unsigned[i + 1..])),
+        None => (unsigned, None),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    let exponent_ok = exponent.is_none_or(|exp| {
+        let digits = exp.strip_prefix(['+', '-']).unwrap_or(exp);
+        !digits.is_empty() && is_digits(digits)
+    });
+    let leading_zero = int.len() > 1 && int.starts_with('0');
+
+    is_digits(int)
+        && is_digits(frac)
+        && !(int.is_empty() && frac.is_empty())
+        && !leading_zero
+        && exponent_ok
+}
+
+/// Returns `true` if `raw` is a valid JSON number.
+pub(crate) fn is_valid(raw: &str) -> bool {
+    is_js_decimal(raw) && Parts::parse(raw).is_ok()
+}
+
+/// Converts hexadecimal digits to decimal ones, whatever their number.
+pub(crate) fn hex_to_decimal(hex: &str) -> String {
+    const BASE: u64 = 1_000_000_000;
+
+    // base 10^9 limbs, least significant first
+    let mut limbs: Vec<u64> = vec![0];
+    for digit in hex.chars().filter_map(|c| c.to_digit(16)) {
+        let mut carry = u64::from(digit);
+        for limb in &mut limbs {
+            let value = *limb * 16 + carry;
+            *limb = value % BASE;
+            carry = value / BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+
+    let mut limbs = limbs.iter().rev();
+    let mut decimal = limbs.next().map_or_else(String::new, u64::to_string);
+    for limb in limbs {
+        decimal.push_str(&format!("{limb:09}"));
+    }
+    decimal
+}
+
+/// Why a number can't be converted by the numeric accessors of [`Json`](crate::Json).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NumberError {
+    /// The value is not a number (or not a valid number lexeme).
+    NotANumber,
+    /// The number has a fractional part, but an integer was requested.
+    NotAnInteger,
+    /// The number is too large (or too small) for the requested type.
+    OutOfRange,
+    /// The requested type can't hold every digit of the number.
+    PrecisionLoss,
+}
+
+impl fmt::Display for NumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NotANumber => "not a number",
+            Self::NotAnInteger => "number is not an integer",
+            Self::OutOfRange => "number out of range",
+            Self::PrecisionLoss => "number can't be represented exactly",
+        })
+    }
+}
+
+impl Error for NumberError {}
+
+/// The exact value of a number: `mantissa × 10^exponent`.
+///
+/// Trailing zeros are moved to the exponent, so equal numbers have equal parts
+/// (`1.50`, `15e-1` and `0.15E1` are all `15 × 10^-1`, and zero is `0 × 10^0`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    pub mantissa: i128,
+    pub exponent: i32,
+}
+
+/// A valid number lexeme, split into its significant digits and power of ten.
+#[derive(PartialEq)]
+struct Parts<'n> {
+    negative: bool,
+    /// Without leading or trailing zeros; empty for zero.
+    digits: Cow<'n, str>,
+    exponent: i64,
+}
+
+impl<'n> Parts<'n> {
+    fn parse(lex: &'n str) -> Result<Self, NumberError> {
+        let (negative, rest) = match lex.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, lex),
+        };
+
+        let (mantissa, exponent) = match rest.find(['e', 'E']) {
+            Some(i) => (&rest[..i], Some(&rest[i + 1..])),
+            None => (rest, None),
+        };
+        let (int, frac) = match mantissa.split_once('.') {
+            Some((int, frac)) => (int, Some(frac)),
+            None => (mantissa, None),
+        };
+
+        let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        if !is_digits(int) || !frac.is_none_or(is_digits) {
+            return Err(NumberError::NotANumber);
+        }
+
+        let exponent = match exponent {
+            None => 0,
+            Some(exp) => {
+                let digits = exp.strip_prefix(['-', '+']).unwrap_or(exp);
+                if !is_digits(digits) {
+                    return Err(NumberError::NotANumber);
+                }
+                // beyond any type anyway, so saturate instead of overflowing
+                let value = digits
+                    .parse::<i64>()
+                    .unwrap_or(i64::MAX / 2)
+                    .min(i64::MAX / 2);
+                if exp.starts_with('-') { -value } else { value }
+            }
+        };
+
+        let frac = frac.unwrap_or("");
+        let digits = match frac {
+            "" => Cow::Borrowed(int),
+            _ => Cow::Owned(format!("{int}{frac}")),
+        };
+
+        // move trailing zeros to the exponent, and drop leading ones
+        let significant = digits.trim_end_matches('0').len();
+        let exponent = exponent - frac.len() as i64 + (digits.len() - significant) as i64;
+        let digits = match digits {
+            Cow::Borrowed(digits) => Cow::Borrowed(digits[..significant].trim_start_matches('0')),
+            Cow::Owned(digits) => Cow::Owned(digits[..significant].trim_start_matches('0').into()),
+        };
+        let exponent = if digits.is_empty() { 0 } else { exponent };
+
+        Ok(Self {
+            negative: negative && !digits.is_empty(),
+            digits,
+            exponent,
+        })
+    }
+
+    /// The number as an integer, or an error if it has a fractional part or exceeds `i128`.
+    fn to_i128(&self) -> Result<i128, NumberError> {
+        if self.exponent < 0 {
+            return Err(NumberError::NotAnInteger);
+        }
+        if self.digits.len() as i64 + self.exponent > 39 {
+            return Err(NumberError::OutOfRange);
+        }
+
+        let mut magnitude: u128 = 0;
+        let zeros = std::iter::repeat_n(b'0', self.exponent as usize);
+        for digit in self.digits.bytes().chain(zeros) {
+            magnitude = magnitude
+                .checked_mul(10)
+                .and_then(|m| m.checked_add(u128::from(digit - b'0')))
+                .ok_or(NumberError::OutOfRange)?;
+        }
+
+        if self.negative {
+            0i128.checked_sub_unsigned(magnitude)
+        } else {
+            i128::try_from(magnitude).ok()
+        }
+        .ok_or(NumberError::OutOfRange)
+    }
+}
+
+pub(crate) fn to_i128(lex: &str) -> Result<i128, NumberError> {
+    Parts::parse(lex)?.to_i128()
+}
+
+pub(crate) fn to_int<T: TryFrom<i128>>(lex: &str) -> Result<T, NumberError> {
+    T::try_from(to_i128(lex)?).map_err(|_| NumberError::OutOfRange)
+}
+
+pub(crate) fn is_integer(lex: &str) -> bool {
+    Parts::parse(lex).is_ok_and(|parts| parts.exponent >= 0)
+}
+
+/// The closest double, or an error unless it is exactly the number as written
+/// (i.e. prints back to the same decimal value).
+pub(crate) fn to_f64(lex: &str) -> Result<f64, NumberError> {
+    let parts = Parts::parse(lex)?;
+    let value: f64 = lex.parse().map_err(|_| NumberError::NotANumber)?;
+
+    if !value.is_finite() {
+        return Err(NumberError::OutOfRange);
+    }
+    if Parts::parse(&format!("{value:e}"))? != parts {
+        return Err(NumberError::PrecisionLoss);
+    }
+    Ok(value)
+}
+
+pub(crate) fn to_decimal(lex: &str) -> Result<Decimal, NumberError> {
+    let parts = Parts::parse(lex)?;
+    let exponent = i32::try_from(parts.exponent).map_err(|_| NumberError::OutOfRange)?;
+    let mantissa = Parts {
+        exponent: 0,
+        ..parts
+    }
+    .to_i128()
+    .map_err(|_| NumberError::PrecisionLoss)?;
+
+    Ok(Decimal { mantissa, exponent })
+}
@@ -0,0 +1,448 @@
+/// What to do when an object contains the same key more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    /// Keep the last value and move the key to its last position (default).
+    #[default]
+    LastWins,
+    /// Keep the first value and ignore later occurrences.
+    FirstWins,
+    /// Keep every value, in an array under the first occurrence of the key.
+    KeepAll,
+    /// Keep every value, under `key#2`, `key#3`... for the later occurrences.
+    Rename,
+    /// Reject the input in [`parse_strict_with`](crate::parse_strict_with).
+    ///
+    /// The repairing functions can't fail, so they keep the last value as with
+    /// [`LastWins`](Self::LastWins). Like with every policy, the duplicates are reported in
+    /// [`fix_with_report`](crate::fix_with_report).
+    Error,
+}
+
+/// Syntax the input is written in. The output is always standard JSON.
+///
+/// Syntax that is valid in the dialect is converted without being reported as a repair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Dialect {
+    /// JSON, repairing what is broken (default).
+    #[default]
+    Json,
+    /// [JSON5](https://spec.json5.org): comments, trailing commas, unquoted keys,
+    /// single-quoted strings, hexadecimal numbers, leading `+` and `.`, `Infinity` and `NaN`.
+    ///
+    /// `Infinity`, `-Infinity` and `NaN` have no JSON equivalent, see
+    /// [`FixOptions::non_finite`] for what they become.
+    Json5,
+    /// JavaScript object literals, as copied from a browser console or printed by the MongoDB
+    /// shell: everything [`Json5`](Self::Json5) has, plus `undefined` (see
+    /// [`FixOptions::undefined`]), regular expressions (`/x+/g` → `"/x+/g"`), and calls wrapping
+    /// a value, like `new Date("2024-01-01")`, `ISODate("…")` or `ObjectId("…")`, which become
+    /// the wrapped value.
+    ///
+    /// A call without arguments (`new Date()`) becomes `null`, one with several becomes the
+    /// array of its arguments.
+    JavaScript,
+    /// Python literals, as printed by `repr()`: `None`, `True` and `False`, single-, double- and
+    /// triple-quoted strings with Python escapes and `b`/`r`/`u` prefixes, tuples and sets
+    /// (arrays, `set()` and `frozenset(…)` included), `inf` and `nan` (see
+    /// [`FixOptions::non_finite`]), `#` comments and trailing commas.
+    ///
+    /// Bytes literals (`b'...'`) become the text they encode in UTF-8, or one character per byte
+    /// (`\xff` → `ÿ`) if they are not valid UTF-8. Non-string keys become strings the way
+    /// Python's `json.dumps` writes them (`{1: 2, None: 3}` → `{"1": 2, "null": 3}`), and tuple
+    /// keys, which it can't write, keep their text (`{(1, 2): 3}` → `{"(1, 2)": 3}`).
+    Python,
+    /// Ruby hashes, as printed by `inspect`: `=>` separators, `:symbol` keys and values
+    /// (which become strings), `key: value` pairs and `nil`. Objects such as `#<Foo id: 1>`
+    /// become strings too, so `#` doesn't start a comment.
+    Ruby,
+    /// Java maps and lists, as printed by `toString()`: `{a=1, b=[x, y]}`. Keys and values
+    /// are unquoted: values that aren't `null`, booleans or numbers become strings, up to the
+    /// next `,`, `}` or `]`.
+    Java,
+    /// Go maps, slices and structs, as printed by `fmt`'s `%v` and `%+v`: `map[a:1 b:[x y]]`
+    /// and `{Name:Bob Age:3}`. Elements are separated by spaces, and keys and values are
+    /// unquoted: values that aren't `<nil>`, booleans or numbers become strings, up to the
+    /// next `key:`, `]` or `}` in maps and structs, and to the next space in slices.
+    Go,
+}
+
+impl Dialect {
+    /// `//` and `/* */` comments are valid. They are removed in every dialect, but
+    /// reported as repairs in the others.
+    pub(crate) fn comments(self) -> bool {
+        matches!(self, Self::Json5 | Self::JavaScript)
+    }
+
+    /// `#` comments are valid. Like other comments, they are removed in every dialect.
+    pub(crate) fn hash_comments(self) -> bool {
+        self == Self::Python
+    }
+
+    /// Any Unicode whitespace, including the byte order mark, separates tokens, not only
+    /// spaces, tabs and line breaks.
+    pub(crate) fn unicode_whitespace(self) -> bool {
+        matches!(self, Self::Json5 | Self::JavaScript)
+    }
+
+    /// Comments are kept as text, since unquoted values may contain `#` and `//`.
+    pub(crate) fn comment_free(self) -> bool {
+        self.unquoted_values()
+    }
+
+    /// Strings may be quoted with `'`.
+    pub(crate) fn single_quotes(self) -> bool {
+        matches!(self, Self::Json5 | Self::JavaScript | Self::Python)
+    }
+
+    /// Triple-quoted strings, `b`/`r`/`u` prefixes, and Python escapes: `\'`, `\a`, `\v`,
+    /// octal and `\x`/`\U` escapes, line continuations, and unknown escapes keeping their `\`.
+    pub(crate) fn python_strings(self) -> bool {
+        self == Self::Python
+    }
+
+    /// `None`, `True`, `False` and `set()`, and numbers as object keys.
+    pub(crate) fn python_literals(self) -> bool {
+        self == Self::Python
+    }
+
+    /// `(…)` tuples and `{…}` sets are arrays.
+    pub(crate) fn tuples(self) -> bool {
+        self == Self::Python
+    }
+
+    /// Object keys may be unquoted identifiers.
+    pub(crate) fn identifier_keys(self) -> bool {
+        matches!(self, Self::Json5 | Self::JavaScript | Self::Ruby)
+    }
+
+    /// Separator between keys and values, besides `:`.
+    pub(crate) fn key_separator(self) -> &'static str {
+        match self {
+            Self::Ruby => "=>",
+            Self::Java => "=",
+            _ => ":",
+        }
+    }
+
+    /// `:symbol` keys and values, `#<…>` objects, `nil`, and the `\e` escape.
+    pub(crate) fn ruby_literals(self) -> bool {
+        self == Self::Ruby
+    }
+
+    /// Keys and values may be unquoted text, such as both sides of `{name=John Smith}`.
+    pub(crate) fn unquoted_values(self) -> bool {
+        matches!(self, Self::Java | Self::Go)
+    }
+
+    /// Elements are separated by spaces instead of commas.
+    pub(crate) fn space_separated(self) -> bool {
+        self == Self::Go
+    }
+
+    /// `map[…]` objects.
+    pub(crate) fn go_maps(self) -> bool {
+        self == Self::Go
+    }
+
+    /// Arrays and objects may end with a comma.
+    pub(crate) fn trailing_commas(self) -> bool {
+        matches!(self, Self::Json5 | Self::JavaScript | Self::Python)
+    }
+
+    /// JavaScript (and Ruby) escapes in strings: `\'`, `\v`, `\0`, `\xFF`, line continuations,
+    /// and any other character escaping itself.
+    pub(crate) fn js_escapes(self) -> bool {
+        matches!(self, Self::Json5 | Self::JavaScript | Self::Ruby)
+    }
+
+    /// Numbers may be hexadecimal, start with `+` or `.`, end with `.`, or be
+    /// `Infinity` or `NaN`.
+    pub(crate) fn js_numbers(self) -> bool {
+        matches!(self, Self::Json5 | Self::JavaScript)
+    }
+
+    /// `undefined`, regular expressions and wrapper calls such as `ISODate("…")`.
+    pub(crate) fn js_values(self) -> bool {
+        self == Self::JavaScript
+    }
+}
+
+/// What `undefined` becomes in the [`Dialect::JavaScript`] dialect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Undefined {
+    /// `null` (default).
+    #[default]
+    Null,
+    /// Leave out object members whose value is `undefined`, like `JSON.stringify` does.
+    /// In arrays and at the root, `undefined` still becomes `null`.
+    Omit,
+}
+
+/// What `NaN`, `Infinity` and `-Infinity`, which JSON can't represent, become in the
+/// dialects that have them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinite {
+    /// The strings `"NaN"`, `"Infinity"` and `"-Infinity"` (default).
+    #[default]
+    String,
+    /// `null`, like `JSON.stringify` does.
+    Null,
+}
+
+/// What a bare word in value position that isn't a literal, such as `ok` in
+/// `{"status": ok}`, becomes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BareWords {
+    /// A string, `"ok"` (default).
+    #[default]
+    String,
+    /// Nothing: the object member or array element is left out, at the root it becomes `null`.
+    Drop,
+}
+
+/// How valid numbers are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    /// Keep the number as written in the input (default).
+    #[default]
+    Preserve,
+    /// Rewrite the number the way JavaScript prints it: the shortest form that parses
+    /// back to the same double, with an exponent only below `1e-6` or from `1e21` on
+    /// (e.g. `1.50` → `1.5`, `1E3` → `1000`, `0.0000001` → `1e-7`).
+    ///
+    /// Digits beyond the precision of a double are lost, see
+    /// [`FixOptions::big_integers_as_strings`] to keep large integers intact.
+    Canonical,
+}
+
+/// Line ending used in the printed output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n` (default).
+    #[default]
+    Lf,
+    /// `\r\n`.
+    CrLf,
+}
+
+impl LineEnding {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Highest [`FixOptions::max_depth`].
+const MAX_DEPTH: usize = 1024;
+
+/// Options for [`fix_with`](crate::fix_with) and [`fix_value_with`](crate::fix_value_with).
+///
+/// The defaults match [`fix`](crate::fix).
+///
+/// ```
+/// use fjson_core::{DuplicateKeys, FixOptions};
+///
+/// let options = FixOptions::new()
+///     .indent("  ")
+///     .deserialize_nested(false)
+///     .duplicate_keys(DuplicateKeys::FirstWins);
+///
+/// assert_eq!(fjson_core::fix_with(r#"{"a": 1, "a": 2"#, &options), "{\n  \"a\": 1\n}");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixOptions {
+    pub(crate) dialect: Dialect,
+    pub(crate) undefined: Undefined,
+    pub(crate) non_finite: NonFinite,
+    pub(crate) bare_words: BareWords,
+    pub(crate) deserialize_nested: bool,
+    pub(crate) indent: String,
+    pub(crate) minify: bool,
+    pub(crate) max_width: Option<usize>,
+    pub(crate) trailing_newline: bool,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) normalize_numbers: bool,
+    pub(crate) leading_zeros_as_strings: bool,
+    pub(crate) number_format: NumberFormat,
+    pub(crate) big_integers_as_strings: bool,
+    pub(crate) duplicate_keys: DuplicateKeys,
+    pub(crate) max_depth: usize,
+}
+
+impl Default for FixOptions {
+    fn default() -> Self {
+        Self {
+            dialect: Dialect::default(),
+            undefined: Undefined::default(),
+            non_finite: NonFinite::default(),
+            bare_words: BareWords::default(),
+            deserialize_nested: true,
+            indent: String::from("   "),
+            minify: false,
+            max_width: None,
+            trailing_newline: false,
+            line_ending: LineEnding::default(),
+            normalize_numbers: true,
+            leading_zeros_as_strings: false,
+            number_format: NumberFormat::default(),
+            big_integers_as_strings: false,
+            duplicate_keys: DuplicateKeys::default(),
+            max_depth: 512,
+        }
+    }
+}
+
+impl FixOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Syntax of the input.
+    ///
+    /// Default: [`Dialect::Json`].
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// What `undefined` becomes in the [`Dialect::JavaScript`] dialect.
+    ///
+    /// Default: [`Undefined::Null`].
+    pub fn undefined(mut self, undefined: Undefined) -> Self {
+        self.undefined = undefined;
+        self
+    }
+
+    /// What `NaN`, `Infinity` and `-Infinity` become in the dialects that have them.
+    ///
+    /// Default: [`NonFinite::String`].
+    pub fn non_finite(mut self, non_finite: NonFinite) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+
+    /// What a bare word in value position that isn't a literal becomes. Misspelled, truncated
+    /// or wrongly cased literals (`tru`, `NULL`) and `None`, `nil` and `undefined` are still
+    /// read as `true`, `false` and `null`.
+    ///
+    /// Default: [`BareWords::String`].
+    pub fn bare_words(mut self, bare_words: BareWords) -> Self {
+        self.bare_words = bare_words;
+        self
+    }
+
+    /// Parse string values that look like JSON (start with `{` or `[`) as nested JSON.
+    ///
+    /// Default: `true`.
+    pub fn deserialize_nested(mut self, enabled: bool) -> Self {
+        self.deserialize_nested = enabled;
+        self
+    }
+
+    /// Indentation used for each nesting level of the pretty-printed output.
+    ///
+    /// Default: three spaces.
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = indent.into();
+        self
+    }
+
+    /// Print everything on a single line without any whitespace (e.g. `{"a":[1,2]}`).
+    ///
+    /// [`indent`](Self::indent) and [`line_ending`](Self::line_ending) are then only used
+    /// for the trailing newline, if any.
+    ///
+    /// Default: `false`.
+    pub fn minify(mut self, enabled: bool) -> Self {
+        self.minify = enabled;
+        self
+    }
+
+    /// Keep arrays and objects on a single line (e.g. `[1, 2]`) when they fit within
+    /// `max_width` characters, indentation included, and only break the others.
+    ///
+    /// Default: unset, every non-empty array and object is broken over lines.
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// End the output with a line ending.
+    ///
+    /// Default: `false`.
+    pub fn trailing_newline(mut self, enabled: bool) -> Self {
+        self.trailing_newline = enabled;
+        self
+    }
+
+    /// Line ending used between lines of the pretty-printed output and for the trailing newline.
+    ///
+    /// Default: [`LineEnding::Lf`].
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Rewrite malformed numbers into valid ones (e.g. `007` → `7`, `1.` → `1.0`, `.5` → `0.5`).
+    ///
+    /// When disabled, malformed numbers are kept verbatim as strings. Valid numbers are never changed.
+    ///
+    /// Default: `true`.
+    pub fn normalize_numbers(mut self, enabled: bool) -> Self {
+        self.normalize_numbers = enabled;
+        self
+    }
+
+    /// Keep unquoted digits with leading zeros, like ZIP codes or account IDs, as strings
+    /// (e.g. `00501` → `"00501"`) instead of normalizing them (`501`).
+    ///
+    /// Only plain digit runs are affected: `-007` and `00.5` are still normalized.
+    ///
+    /// Default: `false`.
+    pub fn leading_zeros_as_strings(mut self, enabled: bool) -> Self {
+        self.leading_zeros_as_strings = enabled;
+        self
+    }
+
+    /// How valid (or repaired) numbers are printed.
+    ///
+    /// Default: [`NumberFormat::Preserve`].
+    pub fn number_format(mut self, format: NumberFormat) -> Self {
+        self.number_format = format;
+        self
+    }
+
+    /// Turn integers beyond ±(2^53 - 1) into strings (e.g. `12345678901234567890` →
+    /// `"12345678901234567890"`), so JavaScript consumers don't round them to the closest double.
+    ///
+    /// Default: `false`.
+    pub fn big_integers_as_strings(mut self, enabled: bool) -> Self {
+        self.big_integers_as_strings = enabled;
+        self
+    }
+
+    /// Policy for keys repeated within the same object.
+    ///
+    /// Default: [`DuplicateKeys::LastWins`].
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Maximum number of nested arrays and objects, counting containers in nested JSON strings.
+    ///
+    /// A container nested deeper is replaced by `null` (its contents are skipped), so hostile
+    /// inputs like `[[[[…` can't exhaust the stack of code walking the [`Json`](crate::Json) tree.
+    ///
+    /// Dropping, cloning, comparing and serializing a [`Json`](crate::Json) recurse into it, so
+    /// the depth is capped at `1024`, which they handle on a 2 MiB thread stack.
+    ///
+    /// Default: `512`.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth.min(MAX_DEPTH);
+        self
+    }
+}
@@ -0,0 +1,2616 @@
+use std::borrow::Cow;
+use std::mem;
+
+use crate::map::{Change, Map, MapBuilder};
+use crate::report::{Diagnostic, RepairKind};
+use crate::{
+    BareWords, DuplicateKeys, FixOptions, Json, NonFinite, NumberFormat, Undefined, number,
+};
+
+/// Forgiving parser.
+///
+/// Containers are kept on an explicit stack instead of the call stack, so parsing
+/// can stop at the end of the buffered input and resume when more is [`feed`](Self::feed)
+/// (see [`StreamParser`](crate::StreamParser)). Once the input is complete, truncated
+/// values are closed the same way a one-shot [`fix`](crate::fix) would.
+///
+/// Strings and numbers that need no changes borrow from a borrowed `input`.
+#[derive(Clone)]
+pub(crate) struct Parser<'a, 'o> {
+    /// Buffered input. Owned when streaming, so consumed input can be dropped.
+    input: Cow<'a, str>,
+    /// Byte cursor into `input`.
+    i: usize,
+    /// Byte offset of the cursor in the whole input (`i` plus the dropped input).
+    pos: usize,
+    /// No more input will be fed, so truncated values are closed instead of awaited.
+    eof: bool,
+    options: Cow<'o, FixOptions>,
+    diagnostics: Vec<Diagnostic>,
+    stack: Vec<Frame<'a>>,
+    /// String interrupted by the end of the buffered input.
+    string: Option<PendingString>,
+    /// Comment interrupted by the end of the buffered input.
+    comment: Option<Comment>,
+    /// The dropped input ends at the start of a line, give or take spaces and tabs.
+    line_start: bool,
+    /// Run of skipped characters, as `(offset, count)`.
+    skipped: Option<(usize, usize)>,
+    root: Option<Json<'a>>,
+    /// Containers enclosing the input, when it is a nested JSON string.
+    depth: usize,
+}
+
+#[derive(Clone)]
+enum Frame<'a> {
+    Array(ArrayFrame<'a>),
+    Object(ObjectFrame<'a>),
+    /// A container nested deeper than `max_depth`, skipped and replaced by `null`.
+    Skip(SkipFrame),
+}
+
+#[derive(Clone)]
+struct ArrayFrame<'a> {
+    start: usize,
+    arr: Vec<Json<'a>>,
+    /// Character closing the array: `]`, or `)` and `}` for tuples and sets.
+    close: char,
+    /// The arguments of a call wrapping a value, such as `ISODate(…)`.
+    call: bool,
+    after_value: bool,
+    comma: Option<usize>,
+    /// Skip anything up to the next `,` or `]` before reading on.
+    skip: bool,
+}
+
+#[derive(Clone)]
+struct ObjectFrame<'a> {
+    start: usize,
+    obj: MapBuilder<'a>,
+    /// Character closing the object: `}`, or `]` for Go maps.
+    close: char,
+    /// Offset of the key being read.
+    key_start: usize,
+    after_value: bool,
+    comma: Option<usize>,
+    state: ObjectState<'a>,
+}
+
+#[derive(Clone)]
+enum ObjectState<'a> {
+    Key,
+    /// Skipping characters that cannot start a key.
+    Skip(RepairKind),
+    /// Expecting the `:` after a key. The flag is set for an unquoted key that isn't valid
+    /// in the dialect, which is only kept (and reported) if a `:` or `=` follows.
+    Colon(Cow<'a, str>, bool),
+    Value(Cow<'a, str>),
+}
+
+#[derive(Clone)]
+struct SkipFrame {
+    /// Brackets opened and not yet closed.
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Comment {
+    /// `// …` or `# …`, up to the end of the line.
+    Line,
+    /// `/* … */`, where `star` is set if the last character read was `*`.
+    Block { start: usize, star: bool },
+}
+
+#[derive(Clone)]
+struct PendingString {
+    start: usize,
+    quote: u8,
+    /// Python string flags: `'''…'''`, `r'…'` and `b'…'`.
+    triple: bool,
+    raw: bool,
+    bytes: bool,
+    lex: String,
+    control: Option<usize>,
+}
+
+enum Parsed<'a> {
+    Value(Json<'a>),
+    /// Progress was made without completing a value (e.g. a container was opened).
+    Continue,
+    /// The end of the buffered input was reached.
+    Pending,
+    /// A value left out of the output (`undefined` with [`Undefined::Omit`]).
+    Omitted,
+    /// A value left out of objects and arrays alike (a bare word with [`BareWords::Drop`]).
+    Dropped,
+}
+
+impl<'a> From<Json<'a>> for Parsed<'a> {
+    fn from(json: Json<'a>) -> Self {
+        Parsed::Value(json)
+    }
+}
+
+/// The value of an unfinished input, kept between [`Parser::snapshot`] calls so the
+/// completed children of the open containers are only copied (and deserialized) once.
+#[derive(Clone, Default)]
+pub(crate) struct Snapshot<'a> {
+    /// The last snapshot, holding the children of the first `assembled` levels.
+    value: Option<Json<'a>>,
+    /// Copies of the open arrays and objects, outermost first.
+    levels: Vec<Level<'a>>,
+    assembled: usize,
+    /// Set once the root value is complete, as it won't change anymore.
+    done: bool,
+}
+
+#[derive(Clone)]
+struct Level<'a> {
+    /// `start` of the copied frame.
+    start: usize,
+    children: Children<'a>,
+    /// Children of the frame copied so far (entry positions, for objects).
+    copied: usize,
+    /// [`MapBuilder::compactions`] when the object was copied, and how many of its
+    /// [`changes`](MapBuilder::changes) the copy follows.
+    compactions: usize,
+    changes: usize,
+    /// Children of the level in the assembled snapshot, before the new ones.
+    shown: usize,
+    /// How the new child of an object was added, if its key repeats a copied one.
+    undo: Option<Undo<'a>>,
+}
+
+#[derive(Clone)]
+enum Undo<'a> {
+    /// The new value was left out (with [`DuplicateKeys::FirstWins`]).
+    Dropped(Option<Json<'a>>),
+    /// The entry moved last with the new value, from this position and old value.
+    Moved(usize, Json<'a>),
+    /// The new value was added to the values of the key, which were `wrapped` in an array
+    /// for it.
+    Kept(Cow<'a, str>, bool),
+}
+
+#[derive(Clone)]
+enum Children<'a> {
+    Array(Vec<Json<'a>>),
+    Object(Map<'a>),
+}
+
+impl<'a> Level<'a> {
+    /// Returns an empty copy of `frame`, if it is an array or object that finishes as itself.
+    fn new(frame: &Frame<'a>) -> Option<Self> {
+        let (start, children) = match frame {
+            Frame::Array(frame) if !frame.call => (frame.start, Children::Array(Vec::new())),
+            Frame::Object(frame) => (frame.start, Children::Object(Map::new())),
+            _ => return None,
+        };
+        Some(Self {
+            start,
+            children,
+            copied: 0,
+            compactions: 0,
+            changes: 0,
+            shown: 0,
+            undo: None,
+        })
+    }
+
+    fn copies(&self, frame: &Frame<'a>) -> bool {
+        match (frame, &self.children) {
+            (Frame::Array(frame), Children::Array(_)) => frame.start == self.start && !frame.call,
+            (Frame::Object(frame), Children::Object(_)) => frame.start == self.start,
+            _ => false,
+        }
+    }
+
+    /// Copies the children `frame` completed since the last update.
+    fn update(&mut self, frame: &Frame<'a>, options: &FixOptions, depth: usize) {
+        match (frame, &mut self.children) {
+            (Frame::Array(frame), Children::Array(copy)) => {
+                copy.extend(
+                    frame.arr[self.copied..]
+                        .iter()
+                        .map(|child| crate::deserialize_at(child.clone(), options, depth)),
+                );
+                self.copied = frame.arr.len();
+            }
+            (Frame::Object(frame), Children::Object(copy)) => {
+                let map = frame.obj.map();
+                if frame.obj.compactions() != self.compactions {
+                    *copy = Map::new();
+                    self.copied = 0;
+                    self.compactions = frame.obj.compactions();
+                    self.changes = frame.obj.changes().len();
+                }
+
+                // follow the changes duplicate keys made to the copied entries
+                for change in &frame.obj.changes()[self.changes..] {
+                    match change {
+                        Change::Removed(key) => {
+                            copy.remove(key);
+                        }
+                        Change::Updated(pos) if *pos < self.copied => {
+                            if let Some((key, value)) = map.entry_at(*pos) {
+                                let value = crate::deserialize_at(value.clone(), options, depth);
+                                copy.insert(key.clone(), value);
+                            }
+                        }
+                        Change::Updated(_) => {}
+                    }
+                }
+                self.changes = frame.obj.changes().len();
+
+                for (key, value) in map.iter_from(self.copied) {
+                    let value = crate::deserialize_at(value.clone(), options, depth);
+                    copy.insert(key.clone(), value);
+                }
+                self.copied = map.positions();
+            }
+            _ => unreachable!("levels only copy matching frames"),
+        }
+    }
+}
+
+impl<'a> Snapshot<'a> {
+    /// Moves the copied children back out of the last snapshot.
+    fn disassemble(&mut self) {
+        let mut node = self.value.take();
+        for level in &mut self.levels[..self.assembled] {
+            node = match (node, &mut level.children) {
+                (Some(Json::Array(mut arr)), Children::Array(copy)) => {
+                    let mut new = arr.split_off(level.shown);
+                    *copy = arr;
+                    new.pop()
+                }
+                (Some(Json::Object(mut map)), Children::Object(copy)) => {
+                    let last = match level.undo.take() {
+                        Some(Undo::Dropped(value)) => value,
+                        Some(Undo::Moved(pos, old)) => Some(map.unmove_last(pos, old)),
+                        Some(Undo::Kept(key, wrapped)) => {
+                            let slot = map.get_mut(&key).expect("kept keys exist");
+                            let Json::Array(values) = slot else {
+                                unreachable!("kept values are arrays");
+                            };
+                            let last = values.pop();
+                            if wrapped {
+                                *slot = values.pop().expect("wrapped with the old value");
+                            }
+                            last
+                        }
+                        None => {
+                            let mut last = None;
+                            while map.len() > level.shown {
+                                let (_, value) = map.pop().expect("longer than shown");
+                                last.get_or_insert(value);
+                            }
+                            last
+                        }
+                    };
+                    *copy = map;
+                    last
+                }
+                _ => unreachable!("assembled levels hold their copies"),
+            };
+        }
+        self.assembled = 0;
+    }
+
+    /// Puts the copied children of the levels back around `finished`, the value of a
+    /// parser whose first levels had no children. Returns `None` if it can't.
+    fn assemble(
+        &mut self,
+        finished: Json<'a>,
+        frames: &[Frame<'a>],
+        options: &FixOptions,
+    ) -> Option<Json<'a>> {
+        // the children added to each level when it was finished, the last of which is the
+        // next level, if any
+        let mut added = Vec::with_capacity(self.levels.len());
+        let mut node = Some(finished);
+        for (i, level) in self.levels.iter().enumerate() {
+            let last = i + 1 == self.levels.len();
+            let (children, next) = match (node?, &level.children) {
+                (Json::Array(mut arr), Children::Array(_)) => {
+                    let next = if last { None } else { Some((None, arr.pop()?)) };
+                    (Children::Array(arr), next)
+                }
+                (Json::Object(mut map), Children::Object(copy)) => {
+                    let next = if last {
+                        None
+                    } else {
+                        let (key, value) = map.pop()?;
+                        Some((Some(key), value))
+                    };
+                    // a repeated key is only handled alone
+                    let repeated = map
+                        .keys()
+                        .chain(next.iter().flat_map(|(key, _)| key.as_deref()))
+                        .any(|key| copy.contains_key(key));
+                    if repeated && map.len() + usize::from(next.is_some()) > 1 {
+                        return None;
+                    }
+                    (Children::Object(map), next)
+                }
+                _ => return None,
+            };
+            added.push((children, next.as_ref().and_then(|(key, _)| key.clone())));
+            node = next.map(|(_, value)| value);
+        }
+
+        let mut inner: Option<Json<'a>> = None;
+        for (depth, level) in self.levels.iter_mut().enumerate().rev() {
+            let (children, key) = added.pop().expect("one per level");
+            let deserialize = |json| crate::deserialize_at(json, options, depth + 1);
+            inner = Some(match (children, &mut level.children) {
+                (Children::Array(new), Children::Array(copy)) => {
+                    let mut arr = mem::take(copy);
+                    level.shown = arr.len();
+                    arr.extend(new.into_iter().map(deserialize));
+                    arr.extend(inner.take());
+                    Json::Array(arr)
+                }
+                (Children::Object(new), Children::Object(copy)) => {
+                    let Frame::Object(frame) = &frames[depth] else {
+                        unreachable!("levels copy their frames");
+                    };
+                    let mut map = mem::take(copy);
+                    level.shown = map.len();
+                    let new = new
+                        .into_iter()
+                        .map(|(key, value)| (key, deserialize(value)));
+                    let chain = key.zip(inner.take());
+                    for (key, value) in new.chain(chain) {
+                        level.undo = push(&mut map, &frame.obj, key, value, options);
+                    }
+                    Json::Object(map)
+                }
+                _ => unreachable!("checked above"),
+            });
+        }
+        self.assembled = self.levels.len();
+        inner
+    }
+}
+
+/// Adds an entry to the copy of `builder`'s map the way [`MapBuilder::push`] would, and
+/// returns how to undo it if the key repeats.
+fn push<'a>(
+    map: &mut Map<'a>,
+    builder: &MapBuilder<'a>,
+    key: Cow<'a, str>,
+    value: Json<'a>,
+    options: &FixOptions,
+) -> Option<Undo<'a>> {
+    if !map.contains_key(&key) {
+        map.insert(key, value);
+        return None;
+    }
+
+    match options.duplicate_keys {
+        DuplicateKeys::LastWins | DuplicateKeys::Error => {
+            let (pos, old) = map.move_last(key, value);
+            Some(Undo::Moved(pos, old))
+        }
+        DuplicateKeys::FirstWins => Some(Undo::Dropped(Some(value))),
+        DuplicateKeys::KeepAll => {
+            let slot = map.get_mut(&key).expect("checked above");
+            let wrapped = builder.repeats(&key) == 1;
+            if wrapped {
+                *slot = Json::Array(vec![mem::replace(slot, Json::Null)]);
+            }
+            if let Json::Array(values) = slot {
+                values.push(value);
+            }
+            Some(Undo::Kept(key, wrapped))
+        }
+        DuplicateKeys::Rename => {
+            let mut count = builder.repeats(&key);
+            let renamed = loop {
+                count += 1;
+                let renamed = format!("{key}#{count}");
+                if !map.contains_key(&renamed) {
+                    break renamed;
+                }
+            };
+            map.insert(renamed, value);
+            None
+        }
+    }
+}
+
+impl<'a> ArrayFrame<'a> {
+    fn finish(mut self) -> Json<'a> {
+        if !self.call {
+            return Json::Array(self.arr);
+        }
+        match self.arr.len() {
+            0 => Json::Null,
+            1 => self.arr.pop().unwrap_or(Json::Null),
+            _ => Json::Array(self.arr),
+        }
+    }
+}
+
+impl<'a, 'o> Parser<'a, 'o> {
+    pub fn new(input: impl Into<Cow<'a, str>>, options: &'o FixOptions) -> Self {
+        let mut parser = Self::streaming(Cow::Borrowed(options));
+        parser.input = input.into();
+        parser.eof = true;
+        parser
+    }
+
+    /// Creates a parser that expects its input through [`feed`](Self::feed).
+    pub fn streaming(options: Cow<'o, FixOptions>) -> Self {
+        Self {
+            input: Cow::Owned(String::new()),
+            i: 0,
+            pos: 0,
+            eof: false,
+            options,
+            diagnostics: Vec::new(),
+            stack: Vec::new(),
+            string: None,
+            comment: None,
+            line_start: true,
+            skipped: None,
+            root: None,
+            depth: 0,
+        }
+    }
+
+    /// Counts `depth` enclosing containers towards `max_depth`.
+    pub fn at_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn options(&self) -> &FixOptions {
+        &self.options
+    }
+
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+
+    /// Appends `chunk` to the input and parses as far as possible.
+    ///
+    /// Consumed characters are dropped from the buffer.
+    pub fn feed(&mut self, chunk: &str) {
+        self.line_start = self.at_line_start();
+        let buffer = self.input.to_mut();
+        buffer.drain(..self.i);
+        buffer.push_str(chunk);
+        self.i = 0;
+        self.run();
+    }
+
+    pub fn parse_and_fix(&mut self) -> Json<'a> {
+        self.eof = true;
+        self.run();
+        self.root.take().unwrap_or(Json::Null)
+    }
+
+    /// Returns the value of the input so far, as if it ended here, like a clone of the parser
+    /// would from [`parse_and_fix`](Self::parse_and_fix) and deserialization.
+    ///
+    /// Only the children the open containers completed since the last call are copied,
+    /// the others are kept in `snapshot`.
+    pub fn snapshot<'s>(&self, snapshot: &'s mut Snapshot<'a>) -> &'s Json<'a> {
+        if !snapshot.done {
+            snapshot.disassemble();
+            let value = self.take_snapshot(snapshot);
+            snapshot.done = self.stack.is_empty() && self.root.is_some();
+            snapshot.value = Some(value);
+        }
+        snapshot.value.as_ref().expect("set above")
+    }
+
+    fn take_snapshot(&self, snapshot: &mut Snapshot<'a>) -> Json<'a> {
+        // levels copying the open arrays and objects, up to the first other container
+        let kept = snapshot
+            .levels
+            .iter()
+            .zip(&self.stack)
+            .take_while(|(level, frame)| level.copies(frame))
+            .count();
+        snapshot.levels.truncate(kept);
+        for frame in &self.stack[kept..] {
+            match Level::new(frame) {
+                Some(level) => snapshot.levels.push(level),
+                None => break,
+            }
+        }
+        for (depth, level) in snapshot.levels.iter_mut().enumerate() {
+            level.update(&self.stack[depth], &self.options, depth + 1);
+        }
+
+        // finish a copy of the parser without the copied children
+        let copied = snapshot.levels.len();
+        let mut parser = Parser {
+            input: self.input.clone(),
+            i: self.i,
+            pos: self.pos,
+            eof: self.eof,
+            options: self.options.clone(),
+            diagnostics: Vec::new(),
+            stack: self
+                .stack
+                .iter()
+                .enumerate()
+                .map(|(i, frame)| match frame {
+                    Frame::Array(frame) if i < copied => Frame::Array(ArrayFrame {
+                        arr: Vec::new(),
+                        ..*frame
+                    }),
+                    Frame::Object(frame) if i < copied => Frame::Object(ObjectFrame {
+                        obj: MapBuilder::default(),
+                        state: frame.state.clone(),
+                        ..*frame
+                    }),
+                    frame => frame.clone(),
+                })
+                .collect(),
+            string: self.string.clone(),
+            comment: self.comment,
+            line_start: self.line_start,
+            skipped: self.skipped,
+            root: self.root.clone(),
+            depth: self.depth,
+        };
+        let finished = parser.parse_and_fix();
+
+        if copied == 0 {
+            return crate::deserialize(finished, &self.options);
+        }
+        match snapshot.assemble(finished, &self.stack, &self.options) {
+            Some(value) => value,
+            // e.g. an object level got more than one new child
+            None => crate::deserialize(self.clone().parse_and_fix(), &self.options),
+        }
+    }
+
+    /// Returns `input[start..end]`, borrowed if the input is.
+    fn slice(&self, start: usize, end: usize) -> Cow<'a, str> {
+        match &self.input {
+            Cow::Borrowed(input) => Cow::Borrowed(&input[start..end]),
+            Cow::Owned(input) => Cow::Owned(input[start..end].to_string()),
+        }
+    }
+
+    fn repair(&mut self, kind: RepairKind, offset: usize, message: impl Into<String>) {
+        self.diagnostics
+            .push(Diagnostic::new(kind, offset, message));
+    }
+
+    fn peek(&self) -> Option<char> {
+        let byte = *self.input.as_bytes().get(self.i)?;
+
+        if byte.is_ascii() {
+            Some(byte as char)
+        } else {
+            self.input[self.i..].chars().next()
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        if let Some(c) = c {
+            self.i += c.len_utf8();
+            self.pos += c.len_utf8();
+        }
+        c
+    }
+
+    /// Returns `true` if the buffered input ran out but more may still be fed.
+    fn starved(&self) -> bool {
+        !self.eof && self.i >= self.input.len()
+    }
+
+    /// Returns whether the input continues with `word`, or `None` if the buffered input
+    /// is too short to tell.
+    fn lookahead(&self, word: &str) -> Option<bool> {
+        let rest = &self.input[self.i..];
+        if rest.starts_with(word) {
+            Some(true)
+        } else if !self.eof && word.starts_with(rest) {
+            None
+        } else {
+            Some(false)
+        }
+    }
+
+    /// Moves the cursor `len` bytes forward.
+    fn advance(&mut self, len: usize) {
+        self.i += len;
+        self.pos += len;
+    }
+
+    /// Skips whitespace, byte order marks and comments, reporting the comments the
+    /// dialect doesn't allow. Where a `value` is expected, a `#` only starts a comment
+    /// at the start of a line.
+    ///
+    /// Returns `false` if the buffered input ends with a `/` that may start a comment.
+    fn skip_whitespace(&mut self, value: bool) -> bool {
+        loop {
+            if let Some(comment) = self.comment.take() {
+                self.skip_comment(comment);
+                if self.comment.is_some() {
+                    return true;
+                }
+            }
+
+            let (comment, len, allowed) = match self.peek() {
+                Some(' ' | '\t' | '\n' | '\r') => {
+                    self.next();
+                    continue;
+                }
+                Some(c) if c.is_whitespace() || c == '\u{FEFF}' => {
+                    if !self.options.dialect.unicode_whitespace() {
+                        self.repair(
+                            RepairKind::Whitespace,
+                            self.pos,
+                            format!("removed whitespace {c:?}"),
+                        );
+                    }
+                    self.next();
+                    continue;
+                }
+
+                _ if self.options.dialect.comment_free() => return true,
+
+                // Ruby's `#<Foo id: 1>` is a value
+                Some('#') if self.options.dialect.ruby_literals() => {
+                    match self.input.as_bytes().get(self.i + 1) {
+                        Some(b'<') => return true,
+                        None if !self.eof => return false,
+                        _ => (Comment::Line, 1, false),
+                    }
+                }
+
+                Some('#') if self.options.dialect.hash_comments() => (Comment::Line, 1, true),
+
+                // where a value belongs, `#fff` is a bare word unless it starts a line
+                Some('#') if value && !self.at_line_start() => return true,
+
+                Some('#') => (Comment::Line, 1, false),
+
+                Some('/') => match self.input.as_bytes().get(self.i + 1) {
+                    Some(b'/') => (Comment::Line, 2, self.options.dialect.comments()),
+                    Some(b'*') => {
+                        let block = Comment::Block {
+                            start: self.pos,
+                            star: false,
+                        };
+                        (block, 2, self.options.dialect.comments())
+                    }
+                    None if !self.eof => return false,
+                    _ => return true,
+                },
+
+                _ => return true,
+            };
+
+            if !allowed {
+                self.repair(RepairKind::Comment, self.pos, "removed comment");
+            }
+            self.comment = Some(comment);
+            self.advance(len);
+        }
+    }
+
+    /// Skips the rest of `comment`, or up to the end of the buffered input, in which case
+    /// the comment is kept to carry on from there.
+    fn skip_comment(&mut self, comment: Comment) {
+        let bytes = &self.input.as_bytes()[self.i..];
+
+        let (len, rest) = match comment {
+            Comment::Line => match bytes.iter().position(|&b| b == b'\n' || b == b'\r') {
+                Some(len) => (len, None),
+                None => (bytes.len(), Some(comment)),
+            },
+
+            Comment::Block { start, mut star } => {
+                let end = bytes.iter().position(|&b| {
+                    let closed = star && b == b'/';
+                    star = b == b'*';
+                    closed
+                });
+                match end {
+                    Some(end) => (end + 1, None),
+                    None => (bytes.len(), Some(Comment::Block { start, star })),
+                }
+            }
+        };
+
+        self.i += len;
+        self.pos += len;
+
+        match rest {
+            Some(Comment::Block { start, .. }) if self.eof => {
+                self.repair(
+                    RepairKind::UnclosedComment,
+                    start,
+                    "closed unterminated comment",
+                );
+            }
+            _ if self.eof => {}
+            rest => self.comment = rest,
+        }
+    }
+
+    /// Skips characters until one in `stop` (or the end of input) is reached,
+    /// reporting them as a single `kind` repair.
+    ///
+    /// Returns `false` if more input is needed to find the end of the run.
+    fn skip_until(&mut self, stop: &[char], kind: RepairKind) -> bool {
+        if self.skipped.is_none() && !self.skip_whitespace(false) {
+            return false;
+        }
+
+        while let Some(c) = self.peek()
+            && !stop.contains(&c)
+        {
+            self.skipped.get_or_insert((self.pos, 0)).1 += 1;
+            self.next();
+        }
+
+        if self.starved() {
+            return false;
+        }
+
+        if let Some((offset, count)) = self.skipped.take() {
+            let message = match kind {
+                RepairKind::DroppedKey => String::from("dropped non-string key"),
+                RepairKind::TrailingCharacters => String::from("ignored trailing characters"),
+                _ => format!("skipped {count} unexpected characters"),
+            };
+            self.repair(kind, offset, message);
+        }
+
+        true
+    }
+
+    /// Parses until the root value is complete or the buffered input runs out.
+    fn run(&mut self) {
+        loop {
+            let Some(frame) = self.stack.pop() else {
+                if self.root.is_some() {
+                    self.skip_until(&[], RepairKind::TrailingCharacters);
+                    return;
+                }
+
+                match self.parse_value() {
+                    Parsed::Value(json) => self.root = Some(json),
+                    Parsed::Omitted | Parsed::Dropped => self.root = Some(Json::Null),
+                    Parsed::Continue => {}
+                    Parsed::Pending => return,
+                }
+                continue;
+            };
+
+            let parsed = match frame {
+                Frame::Array(arr) => self.step_array(arr),
+                Frame::Object(obj) => self.step_object(obj),
+                Frame::Skip(skip) => self.step_skip(skip),
+            };
+
+            match parsed {
+                Parsed::Value(json) => self.close(json),
+                Parsed::Omitted => self.omit(false),
+                Parsed::Dropped => self.omit(true),
+                Parsed::Continue => {}
+                Parsed::Pending => return,
+            }
+        }
+    }
+
+    /// Hands a completed value to the innermost open container (or the root).
+    fn close(&mut self, json: Json<'a>) {
+        match self.stack.last_mut() {
+            None => self.root = Some(json),
+
+            Some(Frame::Skip(_)) => unreachable!("skipped containers have no values"),
+
+            Some(Frame::Array(frame)) => {
+                frame.arr.push(json);
+                frame.after_value = true;
+                frame.comma = None;
+                frame.skip = !self.options.dialect.space_separated();
+            }
+
+            Some(Frame::Object(frame)) => {
+                let ObjectState::Value(key) = mem::replace(&mut frame.state, ObjectState::Key)
+                else {
+                    unreachable!("object values are only parsed in the value state");
+                };
+                frame.after_value = true;
+                frame.comma = None;
+
+                if let Some(message) = frame.obj.push(key, json, self.options.duplicate_keys) {
+                    let offset = frame.key_start;
+                    self.repair(RepairKind::DuplicateKey, offset, message);
+                }
+            }
+        }
+    }
+
+    /// Leaves out a value: an object member is removed, and so is an array element if
+    /// `from_arrays` is set. Anywhere else the value becomes `null`.
+    fn omit(&mut self, from_arrays: bool) {
+        match self.stack.last_mut() {
+            Some(Frame::Object(frame)) => {
+                frame.state = ObjectState::Key;
+                frame.after_value = true;
+                frame.comma = None;
+            }
+            Some(Frame::Array(frame)) if from_arrays => {
+                frame.after_value = true;
+                frame.comma = None;
+                frame.skip = !self.options.dialect.space_separated();
+            }
+            _ => self.close(Json::Null),
+        }
+    }
+
+    /// Skips whitespace and peeks at the next character, unless a string is
+    /// in progress, in which case `"` is returned so the string is resumed.
+    ///
+    /// Returns `None` at the end of the buffered input, or if it ends where a comment may start.
+    fn peek_token(&mut self) -> Option<char> {
+        self.peek_past_whitespace(false)
+    }
+
+    /// Like [`peek_token`](Self::peek_token), where a value is expected.
+    fn peek_value(&mut self) -> Option<char> {
+        self.peek_past_whitespace(true)
+    }
+
+    fn peek_past_whitespace(&mut self, value: bool) -> Option<char> {
+        if self.string.is_some() {
+            return Some('"');
+        }
+        if !self.skip_whitespace(value) {
+            return None;
+        }
+        self.peek()
+    }
+
+    /// Returns whether only spaces and tabs separate the cursor from the start of its line.
+    fn at_line_start(&self) -> bool {
+        match self.input[..self.i]
+            .trim_end_matches([' ', '\t'])
+            .chars()
+            .next_back()
+        {
+            Some(c) => matches!(c, '\n' | '\r'),
+            None => self.line_start,
+        }
+    }
+
+    fn parse_value(&mut self) -> Parsed<'a> {
+        let Some(c) = self.peek_value() else {
+            if !self.eof {
+                return Parsed::Pending;
+            }
+            self.repair(
+                RepairKind::MissingValue,
+                self.pos,
+                "missing value, using null",
+            );
+            return Parsed::Value(Json::Null);
+        };
+
+        if self.options.dialect.js_numbers() && matches!(c, 'I' | 'N') {
+            match self.lookahead(if c == 'I' { "Infinity" } else { "NaN" }) {
+                Some(true) => return self.parse_scalar(Self::parse_number),
+                Some(false) => {}
+                None => return Parsed::Pending,
+            }
+        }
+
+        if self.options.dialect.js_values() {
+            match c {
+                'u' => match self.lookahead("undefined") {
+                    Some(true) => {
+                        self.advance("undefined".len());
+                        return match self.options.undefined {
+                            Undefined::Null => Parsed::Value(Json::Null),
+                            Undefined::Omit => Parsed::Omitted,
+                        };
+                    }
+                    Some(false) => {}
+                    None => return Parsed::Pending,
+                },
+                '/' => return self.parse_scalar(Self::parse_regex),
+                _ => {}
+            }
+
+            if c != '\\' && is_identifier_start(c) {
+                match self.wrapper_call() {
+                    Some(0) => {}
+                    Some(len) => return self.open_call(len),
+                    None => return Parsed::Pending,
+                }
+            }
+        }
+
+        let python = self.options.dialect.python_literals();
+        let keyword = match c {
+            'N' if python => Some(("None", Json::Null)),
+            'T' if python => Some(("True", Json::True)),
+            'F' if python => Some(("False", Json::False)),
+            // the empty sets
+            's' if python => Some(("set()", Json::Array(Vec::new()))),
+            'f' if python => Some(("frozenset()", Json::Array(Vec::new()))),
+            'n' if self.options.dialect.ruby_literals() => Some(("nil", Json::Null)),
+            _ => None,
+        };
+        if let Some((word, json)) = keyword {
+            match self.lookahead(word) {
+                Some(true) => {
+                    self.advance(word.len());
+                    return Parsed::Value(json);
+                }
+                Some(false) => {}
+                None => return Parsed::Pending,
+            }
+        }
+
+        // `frozenset({1, 2})` is the set it wraps
+        if c == 'f' && python {
+            match self.lookahead("frozenset(") {
+                Some(true) => return self.open_call("frozenset(".len()),
+                Some(false) => {}
+                None => return Parsed::Pending,
+            }
+        }
+
+        if c == '#' && self.options.dialect.ruby_literals() {
+            return self.parse_scalar(Self::parse_inspect);
+        }
+
+        if c == ':' && self.options.dialect.ruby_literals() {
+            return match self.parse_symbol() {
+                Some(name) => Parsed::Value(Json::String(name)),
+                None => Parsed::Pending,
+            };
+        }
+
+        if c == 'm' && self.options.dialect.go_maps() {
+            match self.lookahead("map[") {
+                Some(true) if self.depth + self.stack.len() >= self.options.max_depth => {
+                    return self.skip_too_deep();
+                }
+                Some(true) => return self.open_object("map[".len(), ']'),
+                Some(false) => {}
+                None => return Parsed::Pending,
+            }
+        }
+
+        if self.options.dialect.unquoted_values()
+            && !matches!(c, '"' | '[' | '{')
+            && !self.ends_unquoted(c)
+        {
+            return self.parse_scalar(Self::parse_unquoted);
+        }
+
+        match c {
+            c if self.at_string(c) => match self.parse_string() {
+                Some(lex) => Parsed::Value(Json::String(lex)),
+                None => Parsed::Pending,
+            },
+
+            c if c != '\\' && is_identifier_start(c) => self.parse_scalar(Self::parse_word),
+
+            val if val.is_ascii_digit() || val == '-' || val == '.' => {
+                self.parse_scalar(Self::parse_number_token)
+            }
+            // a `+` not followed by a number is a bare word
+            '+' if self.options.dialect.js_numbers() => {
+                match self.input[self.i + 1..].chars().next() {
+                    Some(c) if c.is_ascii_digit() || matches!(c, '.' | 'I' | 'N') => {
+                        self.parse_scalar(Self::parse_number_token)
+                    }
+                    None if !self.eof => Parsed::Pending,
+                    _ => self.parse_scalar(Self::parse_word),
+                }
+            }
+
+            '[' | '{' | '('
+                if (c != '(' || self.options.dialect.tuples())
+                    && self.depth + self.stack.len() >= self.options.max_depth =>
+            {
+                self.skip_too_deep()
+            }
+
+            '[' | '(' if c == '[' || self.options.dialect.tuples() => {
+                self.stack.push(Frame::Array(ArrayFrame {
+                    start: self.pos,
+                    arr: Vec::new(),
+                    close: if c == '[' { ']' } else { ')' },
+                    call: false,
+                    after_value: false,
+                    comma: None,
+                    skip: false,
+                }));
+                self.next();
+                Parsed::Continue
+            }
+
+            '{' => self.open_object(1, '}'),
+
+            // nothing between a key and the end of its member, e.g. `{"a":}` or `{a=, b=1}`
+            ',' | '}' | ']' if self.ends_member(c) => self.missing_value(),
+
+            c if c != ':' && self.is_bare_token_part(c) => self.parse_scalar(Self::parse_word),
+
+            _ => {
+                let offset = self.pos;
+                self.next();
+                self.repair(
+                    RepairKind::InvalidValue,
+                    offset,
+                    format!("unexpected character {c:?}, using null"),
+                );
+                Parsed::Value(Json::Null)
+            }
+        }
+    }
+
+    /// Returns `true` if `c` ends the member of the innermost object, whose value is read.
+    fn ends_member(&self, c: char) -> bool {
+        matches!(self.stack.last(), Some(Frame::Object(frame)) if c == ',' || c == frame.close)
+    }
+
+    /// Returns the value of a key that has none, leaving what follows it. An empty Java or Go
+    /// value is how they print an empty string, anywhere else it is repaired to `null`.
+    fn missing_value(&mut self) -> Parsed<'a> {
+        if self.options.dialect.unquoted_values() {
+            return Parsed::Value(Json::String(Cow::Borrowed("")));
+        }
+        self.repair(
+            RepairKind::MissingValue,
+            self.pos,
+            "missing value, using null",
+        );
+        Parsed::Value(Json::Null)
+    }
+
+    /// Returns whether the cursor is at the space before another key, as in Go's
+    /// `map[a: b:2]`, where `a` is empty, or `None` if the buffered input is too short to tell.
+    fn at_next_key(&self) -> Option<bool> {
+        let rest = &self.input[self.i..];
+        let token = rest.trim_start();
+        if token.is_empty() && !self.eof {
+            return None;
+        }
+        if token.len() == rest.len() {
+            return Some(false);
+        }
+
+        match token.find(|c| !is_bare_key_part(c)) {
+            Some(len) => Some(len > 0 && token[len..].starts_with(':')),
+            None if self.eof => Some(false),
+            None => None,
+        }
+    }
+
+    /// Opens an object, whose opening (`{` or `map[`) is `len` bytes long.
+    fn open_object(&mut self, len: usize, close: char) -> Parsed<'a> {
+        self.stack.push(Frame::Object(ObjectFrame {
+            start: self.pos,
+            obj: MapBuilder::default(),
+            close,
+            key_start: self.pos,
+            after_value: false,
+            comma: None,
+            state: ObjectState::Key,
+        }));
+        self.advance(len);
+        Parsed::Continue
+    }
+
+    /// Opens the arguments of a call wrapping a value, whose opening (`ISODate(`) is `len`
+    /// bytes long.
+    fn open_call(&mut self, len: usize) -> Parsed<'a> {
+        if self.depth + self.stack.len() >= self.options.max_depth {
+            return self.skip_too_deep();
+        }
+
+        self.stack.push(Frame::Array(ArrayFrame {
+            start: self.pos,
+            arr: Vec::new(),
+            close: ')',
+            call: true,
+            after_value: false,
+            comma: None,
+            skip: false,
+        }));
+        self.advance(len);
+        Parsed::Continue
+    }
+
+    /// Replaces the container at the cursor, nested deeper than `max_depth`, with `null`.
+    fn skip_too_deep(&mut self) -> Parsed<'a> {
+        self.repair(
+            RepairKind::TooDeep,
+            self.pos,
+            format!(
+                "replaced container nested deeper than {} levels with null",
+                self.options.max_depth
+            ),
+        );
+        self.stack.push(Frame::Skip(SkipFrame {
+            depth: 0,
+            in_string: false,
+            escaped: false,
+        }));
+        Parsed::Continue
+    }
+
+    /// Returns the length of a call wrapping a value at the cursor, up to its `(`
+    /// (e.g. `new Date(` or `ObjectId(`), `Some(0)` if there is none, or `None` if the
+    /// buffered input is too short to tell.
+    fn wrapper_call(&self) -> Option<usize> {
+        let rest = &self.input[self.i..];
+
+        let mut len = 0;
+        if let Some(name) = rest.strip_prefix("new")
+            && name.starts_with(char::is_whitespace)
+        {
+            len = rest.len() - name.trim_start().len();
+        }
+
+        let name = rest[len..]
+            .find(|c| !is_identifier_part(c))
+            .unwrap_or(rest.len() - len);
+        len += name;
+        len = rest.len() - rest[len..].trim_start().len();
+
+        match rest[len..].chars().next() {
+            None if !self.eof => None,
+            _ if name == 0 => Some(0),
+            Some('(') => Some(len + 1),
+            _ => Some(0),
+        }
+    }
+
+    /// Returns `true` if `c` ends an unquoted value.
+    fn ends_unquoted(&self, c: char) -> bool {
+        match c {
+            ',' | '}' | ']' | '\n' | '\r' => true,
+            _ => self.options.dialect.space_separated() && c.is_whitespace(),
+        }
+    }
+
+    /// Reads an unquoted value, such as `John Smith` in `{name=John Smith}`. `null`, `nil`,
+    /// `<nil>`, booleans and numbers keep their meaning, anything else becomes a string.
+    ///
+    /// A value of a Go map or struct runs across spaces up to the next `key:`, since they
+    /// are printed without commas (`map[msg:request failed code:500]`).
+    fn parse_unquoted(&mut self) -> Json<'a> {
+        let start = self.i;
+        let close = match self.stack.last() {
+            Some(Frame::Object(frame)) if self.options.dialect.space_separated() => {
+                Some(frame.close)
+            }
+            _ => None,
+        };
+
+        while let Some(c) = self.peek() {
+            if self.ends_unquoted(c) {
+                let Some(close) = close.filter(|_| matches!(c, ' ' | '\t')) else {
+                    break;
+                };
+                match self.at_next_key() {
+                    Some(true) => break,
+                    Some(false) => {
+                        let token = self.input[self.i..].trim_start();
+                        if token.is_empty() || token.starts_with(close) {
+                            break;
+                        }
+                    }
+                    None => {
+                        // the next key may be in the next chunk
+                        self.advance(self.input.len() - self.i);
+                        break;
+                    }
+                }
+            }
+            self.next();
+        }
+
+        let end = start + self.input[start..self.i].trim_end().len();
+        let text = self.slice(start, end);
+        match &*text {
+            "null" | "nil" | "<nil>" => Json::Null,
+            "true" => Json::True,
+            "false" => Json::False,
+            "NaN" => self.non_finite("NaN"),
+            "Infinity" | "Inf" | "+Inf" => self.non_finite("Infinity"),
+            "-Infinity" | "-Inf" => self.non_finite("-Infinity"),
+            _ if number::is_valid(&text) => self.format_number(text),
+            _ => Json::String(text),
+        }
+    }
+
+    /// Reads a Ruby symbol, such as `:name`, `:empty?` or `:"quoted sym"`, as its name.
+    ///
+    /// Returns `None`, without consuming anything, if it may go on in the next chunk. A quoted
+    /// symbol is read as a string, which carries on in the next chunk like any other.
+    fn parse_symbol(&mut self) -> Option<Cow<'a, str>> {
+        let (i, pos) = (self.i, self.pos);
+        self.next();
+
+        let name = match self.peek() {
+            Some('"') => return self.parse_string(),
+            Some(_) => self.parse_identifier(),
+            None => None,
+        };
+        let name = match (name, self.peek()) {
+            (Some(name), Some(c @ ('?' | '!'))) => {
+                self.next();
+                Some(Cow::Owned(format!("{name}{c}")))
+            }
+            (Some(_), None) if !self.eof => None,
+            (name, _) => name,
+        };
+
+        if name.is_none() {
+            self.i = i;
+            self.pos = pos;
+        }
+        name
+    }
+
+    /// Reads a Ruby object as printed by `inspect`, such as `#<Foo id: 1>`, as a string.
+    fn parse_inspect(&mut self) -> Json<'a> {
+        let (start, offset) = (self.i, self.pos);
+
+        let (mut depth, mut quoted, mut escaped, mut prev) = (0, false, false, ' ');
+        while let Some(c) = self.peek() {
+            self.next();
+
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if quoted => escaped = true,
+                '"' => quoted = !quoted,
+                '<' if !quoted && prev == '#' => depth += 1,
+                // not the `>` of a hash's `=>`
+                '>' if !quoted && prev != '=' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Json::String(self.slice(start, self.i));
+                    }
+                }
+                _ => {}
+            }
+            prev = c;
+        }
+
+        self.repair(
+            RepairKind::UnclosedString,
+            offset,
+            "closed unterminated object",
+        );
+        Json::String(self.slice(start, self.i))
+    }
+
+    /// Reads a regular expression literal, such as `/x+/g`, as a string of its source.
+    fn parse_regex(&mut self) -> Json<'a> {
+        let (start, offset) = (self.i, self.pos);
+        self.next();
+
+        let (mut escaped, mut class, mut closed) = (false, false, false);
+        while let Some(c) = self.peek() {
+            if matches!(c, '\n' | '\r') {
+                break;
+            }
+            self.next();
+
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '[' => class = true,
+                ']' => class = false,
+                '/' if !class => {
+                    closed = true;
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        // flags
+        while let Some(c) = self.peek()
+            && c.is_ascii_alphabetic()
+        {
+            self.next();
+        }
+
+        if !closed {
+            self.repair(
+                RepairKind::UnclosedString,
+                offset,
+                "closed unterminated regular expression",
+            );
+        }
+        Json::String(self.slice(start, self.i))
+    }
+
+    /// Runs `parse`, undoing it if it ran into the end of the buffered input,
+    /// since the literal or number may continue in the next chunk.
+    fn parse_scalar<P: Into<Parsed<'a>>>(&mut self, parse: fn(&mut Self) -> P) -> Parsed<'a> {
+        let (i, pos, diagnostics) = (self.i, self.pos, self.diagnostics.len());
+
+        let parsed = parse(self).into();
+
+        if self.starved() {
+            self.i = i;
+            self.pos = pos;
+            self.diagnostics.truncate(diagnostics);
+            return Parsed::Pending;
+        }
+
+        parsed
+    }
+
+    /// Reads a bare word in value position, up to the next whitespace or structural character.
+    /// A case-insensitive prefix of `true`, `false` or `null`, or one of `None`, `nil` and
+    /// `undefined`, is read as that literal. Any other word (`ok`, `10px`, `2024-01-01`)
+    /// becomes a string or is dropped, see [`FixOptions::bare_words`].
+    fn parse_word(&mut self) -> Parsed<'a> {
+        let (start, offset) = (self.i, self.pos);
+        while let Some(c) = self.peek()
+            && self.is_bare_token_part(c)
+        {
+            self.next();
+        }
+
+        let word = self.slice(start, self.i);
+        if self.options.dialect.python_literals() {
+            match &*word {
+                "inf" => return self.non_finite("Infinity").into(),
+                "-inf" => return self.non_finite("-Infinity").into(),
+                "nan" => return self.non_finite("NaN").into(),
+                _ => {}
+            }
+        }
+
+        let lower = word.to_lowercase();
+        let literal = [
+            ("true", Json::True),
+            ("false", Json::False),
+            ("null", Json::Null),
+        ]
+        .into_iter()
+        .find(|(keyword, _)| keyword.starts_with(&lower))
+        .or(match &*lower {
+            "none" | "nil" | "undefined" => Some(("null", Json::Null)),
+            _ => None,
+        });
+
+        if let Some((keyword, json)) = literal {
+            if word != keyword {
+                self.repair(
+                    RepairKind::LiteralRepaired,
+                    offset,
+                    format!("normalized `{word}` to `{keyword}`"),
+                );
+            }
+            return Parsed::Value(json);
+        }
+
+        match self.options.bare_words {
+            BareWords::String => {
+                self.repair(
+                    RepairKind::BareWord,
+                    offset,
+                    format!("quoted bare word {word:?}"),
+                );
+                Parsed::Value(Json::String(word))
+            }
+            BareWords::Drop => {
+                self.repair(
+                    RepairKind::BareWord,
+                    offset,
+                    format!("dropped bare word {word:?}"),
+                );
+                Parsed::Dropped
+            }
+        }
+    }
+
+    /// Reads a number, or a bare word that only starts like one, such as `10px`.
+    fn parse_number_token(&mut self) -> Parsed<'a> {
+        let (i, pos, diagnostics) = (self.i, self.pos, self.diagnostics.len());
+        let json = self.parse_number();
+
+        match self.peek() {
+            Some(c) if self.is_bare_token_part(c) => {
+                self.i = i;
+                self.pos = pos;
+                self.diagnostics.truncate(diagnostics);
+                self.parse_word()
+            }
+            _ => Parsed::Value(json),
+        }
+    }
+
+    /// Returns `true` if `c` can be part of a bare word in value position: anything but
+    /// whitespace, commas, quotes and brackets (and parentheses in the dialects that have them).
+    fn is_bare_token_part(&self, c: char) -> bool {
+        let parens = self.options.dialect.tuples() || self.options.dialect.js_values();
+        match c {
+            ',' | '"' | '[' | ']' | '{' | '}' => false,
+            '(' | ')' => !parens,
+            _ => !c.is_whitespace(),
+        }
+    }
+
+    fn parse_number(&mut self) -> Json<'a> {
+        let (start, start_offset) = (self.i, self.pos);
+        let mut lex = String::new();
+
+        if let Some('+') = self.peek() {
+            self.next();
+        }
+
+        if let Some('-') = self.peek() {
+            lex.push('-');
+            self.next();
+
+            while let Some('-') = self.peek() {
+                self.next();
+            }
+        }
+
+        if self.options.dialect.js_numbers()
+            && let Some(json) = self.parse_js_number(start, start_offset, !lex.is_empty())
+        {
+            return json;
+        }
+
+        if let Some('.') = self.peek() {
+            lex.push('0');
+        }
+
+        // leading 0
+        if let Some('0') = self.peek() {
+            lex.push('0');
+            self.next();
+
+            while let Some(c) = self.peek() {
+                if c == '0' {
+                    self.next();
+                } else {
+                    if c.is_ascii_digit() {
+                        lex.pop();
+                    }
+                    break;
+                }
+            }
+        }
+
+        // integer
+        while let Some(c) = self.peek()
+            && c.is_ascii_digit()
+        {
+            lex.push(c);
+            self.next();
+        }
+
+        // float
+        if let Some('.') = self.peek() {
+            lex.push('.');
+            self.next();
+
+            let mut count = 0;
+            while let Some(c) = self.peek()
+                && c.is_ascii_digit()
+            {
+                lex.push(c);
+                self.next();
+                count += 1;
+            }
+
+            if count == 0 {
+                lex.push('0');
+            }
+        }
+
+        // expoent
+        if let Some(c) = self.peek()
+            && (c == 'e' || c == 'E')
+        {
+            // `-e5` has no digits before its exponent
+            if !lex.ends_with(|c: char| c.is_ascii_digit()) {
+                lex.push('0');
+            }
+            lex.push(c);
+            self.next();
+
+            if let Some(sign) = self.peek()
+                && (sign == '-' || sign == '+')
+            {
+                lex.push(sign);
+                self.next();
+            }
+
+            let mut count = 0;
+            while let Some(c) = self.peek()
+                && c.is_ascii_digit()
+            {
+                lex.push(c);
+                self.next();
+                count += 1;
+            }
+
+            if count == 0 {
+                if lex.ends_with('-') || lex.ends_with('+') {
+                    lex.pop();
+                }
+                lex.pop();
+            }
+        }
+
+        if lex == "-" {
+            lex.push('0');
+        }
+
+        if self.input[start..self.i] == lex && number::is_valid(&lex) {
+            return self.format_number(self.slice(start, self.i));
+        }
+
+        if self.options.dialect.js_numbers() && number::is_js_decimal(&self.input[start..self.i]) {
+            return self.format_number(Cow::Owned(lex));
+        }
+
+        let raw = self.input[start..self.i].to_string();
+
+        if self.options.leading_zeros_as_strings
+            && raw.len() > 1
+            && raw.starts_with('0')
+            && raw.bytes().all(|b| b.is_ascii_digit())
+        {
+            self.repair(
+                RepairKind::NumberRepaired,
+                start_offset,
+                format!("kept `{raw}` as a string to preserve its leading zeros"),
+            );
+            return Json::String(self.slice(start, self.i));
+        }
+
+        if !self.options.normalize_numbers {
+            self.repair(
+                RepairKind::NumberRepaired,
+                start_offset,
+                format!("kept malformed number `{raw}` as a string"),
+            );
+            return Json::String(Cow::Owned(raw));
+        }
+
+        self.repair(
+            RepairKind::NumberRepaired,
+            start_offset,
+            format!("normalized `{raw}` to `{lex}`"),
+        );
+
+        self.format_number(Cow::Owned(lex))
+    }
+
+    /// Parses the numbers only JavaScript has, after their sign: `Infinity`, `NaN` and
+    /// hexadecimal ones. Returns `None` for the others.
+    fn parse_js_number(&mut self, start: usize, offset: usize, negative: bool) -> Option<Json<'a>> {
+        let rest = &self.input[self.i..];
+
+        for word in ["Infinity", "NaN"] {
+            if rest.starts_with(word) {
+                self.advance(word.len());
+                let name = if negative && word == "Infinity" {
+                    "-Infinity"
+                } else {
+                    word
+                };
+                return Some(self.non_finite(name));
+            }
+            if !self.eof && word.starts_with(rest) {
+                // the word may go on in the next chunk: stop at the end of the buffered
+                // input, so `parse_scalar` tries again then
+                self.advance(rest.len());
+                return Some(Json::Null);
+            }
+        }
+
+        if !rest.starts_with("0x") && !rest.starts_with("0X") {
+            return None;
+        }
+        self.advance(2);
+
+        let rest = &self.input[self.i..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(rest.len());
+        let mut lex = match len {
+            0 => String::from("0"),
+            _ => number::hex_to_decimal(&rest[..len]),
+        };
+        if negative && lex != "0" {
+            lex.insert(0, '-');
+        }
+
+        if len == 0 {
+            let raw = &self.input[start..self.i];
+            self.repair(
+                RepairKind::NumberRepaired,
+                offset,
+                format!("normalized `{raw}` to `{lex}`"),
+            );
+        }
+        self.advance(len);
+
+        Some(self.format_number(Cow::Owned(lex)))
+    }
+
+    /// What `NaN`, `Infinity` or `-Infinity` becomes.
+    fn non_finite(&self, name: &'static str) -> Json<'a> {
+        match self.options.non_finite {
+            NonFinite::String => Json::String(Cow::Borrowed(name)),
+            NonFinite::Null => Json::Null,
+        }
+    }
+
+    /// Applies the number options to the valid number `lex`.
+    fn format_number(&self, lex: Cow<'a, str>) -> Json<'a> {
+        if self.options.big_integers_as_strings && number::is_unsafe_integer(&lex) {
+            return Json::String(lex);
+        }
+
+        if self.options.number_format == NumberFormat::Canonical
+            && let Some(canonical) = number::canonical(&lex)
+            && canonical != lex
+        {
+            return Json::Number(Cow::Owned(canonical));
+        }
+
+        Json::Number(lex)
+    }
+
+    /// Returns `true` if a string starts at the next token `c`, or may start there but the
+    /// buffered input is too short to tell (see [`string_prefix`](Self::string_prefix) and
+    /// [`quoted_span`](Self::quoted_span)).
+    fn at_string(&self, c: char) -> bool {
+        match c {
+            '"' => true,
+            '\'' => self.options.dialect.single_quotes() || self.quoted_span() != Some(false),
+            _ => self.string_prefix() != Some(0),
+        }
+    }
+
+    /// Returns whether the `'` at the cursor, in a dialect without single-quoted strings,
+    /// still quotes a string because it is closed on the same line (`'hello world'`), or
+    /// `None` if the buffered input is too short to tell. Otherwise it is part of a bare word.
+    fn quoted_span(&self) -> Option<bool> {
+        let mut escaped = false;
+        for (at, c) in self.input[self.i + 1..].char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '\'' if self.closes_span(self.i + 1 + at)? => return Some(true),
+                '\n' | '\r' => return Some(false),
+                _ => {}
+            }
+        }
+        if self.eof { Some(false) } else { None }
+    }
+
+    /// Returns whether the `'` at `at` closes a single-quoted span, which it only does before
+    /// a delimiter (`'don't'` keeps its apostrophe), or `None` if the buffered input is too
+    /// short to tell.
+    fn closes_span(&self, at: usize) -> Option<bool> {
+        match self.input[at + 1..]
+            .trim_start_matches([' ', '\t', '\n', '\r'])
+            .chars()
+            .next()
+        {
+            Some(c) => Some(matches!(c, ',' | '}' | ']' | ':')),
+            None if self.eof => Some(true),
+            None => None,
+        }
+    }
+
+    /// Returns the length of the Python string prefix at the cursor (`b`, `r`, `u`, `rb`...)
+    /// if a quote follows it, `Some(0)` if there is none, or `None` if the buffered input is
+    /// too short to tell.
+    fn string_prefix(&self) -> Option<usize> {
+        if !self.options.dialect.python_strings() {
+            return Some(0);
+        }
+
+        let rest = &self.input.as_bytes()[self.i..];
+        let len = rest
+            .iter()
+            .take(2)
+            .take_while(|b| matches!(b.to_ascii_lowercase(), b'b' | b'r' | b'u'))
+            .count();
+
+        match rest.get(len) {
+            _ if len == 0 => Some(0),
+            Some(b'\'' | b'"') => Some(len),
+            None if !self.eof => None,
+            _ => Some(0),
+        }
+    }
+
+    /// Returns `None` if the string is interrupted by the end of the buffered input.
+    /// The progress is kept and the next call carries on from there.
+    fn parse_string(&mut self) -> Option<Cow<'a, str>> {
+        // `lex` stays `None` while the contents are a plain slice of the input from `content`
+        let (start, quote, mut lex, mut control, triple, raw, bytes) = match self.string.take() {
+            Some(string) => (
+                string.start,
+                string.quote,
+                Some(string.lex),
+                string.control,
+                string.triple,
+                string.raw,
+                string.bytes,
+            ),
+            None => {
+                let prefix = self.string_prefix()?;
+                if !self.options.dialect.single_quotes() && self.peek() == Some('\'') {
+                    self.quoted_span()?;
+                    self.repair(
+                        RepairKind::SingleQuotes,
+                        self.pos,
+                        "replaced single quotes with double quotes",
+                    );
+                }
+
+                let rest = &self.input.as_bytes()[self.i + prefix..];
+                let quote = rest.first().copied().unwrap_or(b'"');
+
+                let python = self.options.dialect.python_strings();
+                let triple = python && rest.starts_with(&[quote; 3]);
+                if python && !triple && !self.eof && [quote; 3].starts_with(rest) {
+                    // `''` may be an empty string or start a triple-quoted one
+                    return None;
+                }
+
+                let flags = &self.input.as_bytes()[self.i..self.i + prefix];
+                let raw = flags.iter().any(|b| b.eq_ignore_ascii_case(&b'r'));
+                let bytes = flags.iter().any(|b| b.eq_ignore_ascii_case(&b'b'));
+
+                let start = self.pos;
+                self.advance(prefix + if triple { 3 } else { 1 });
+                (start, quote, None, None, triple, raw, bytes)
+            }
+        };
+        let content = self.i;
+        let mut end = None;
+        // a single-quoted span in a dialect without single-quoted strings keeps inner apostrophes
+        let span = quote == b'\'' && !self.options.dialect.single_quotes();
+
+        loop {
+            // plain text is copied (or skipped over) as is, special characters are handled one by one
+            let rest = &self.input.as_bytes()[self.i..];
+            let plain = rest
+                .iter()
+                .position(|&b| b == quote || b == b'\\' || b < b' ')
+                .unwrap_or(rest.len());
+            if let Some(lex) = &mut lex {
+                lex.push_str(&self.input[self.i..self.i + plain]);
+            }
+            self.i += plain;
+            self.pos += plain;
+
+            let Some(c) = self.peek() else {
+                break;
+            };
+            if c == quote as char {
+                let rest = &self.input.as_bytes()[self.i..];
+                let closes = if span {
+                    self.closes_span(self.i)
+                } else {
+                    Some(!triple || rest.starts_with(&[quote; 3]))
+                };
+                let Some(closes) = closes else {
+                    break;
+                };
+                if closes {
+                    end = Some(self.i);
+                    self.advance(if triple { 3 } else { 1 });
+                    break;
+                }
+                if triple && !self.eof && rest.len() < 3 {
+                    // may be the closing quotes
+                    break;
+                }
+
+                if let Some(lex) = &mut lex {
+                    lex.push(c);
+                }
+                self.next();
+                continue;
+            }
+            if c == '\\' && !self.escape_buffered() {
+                break;
+            }
+
+            let lex = lex.get_or_insert_with(|| self.input[content..self.i].to_string());
+            self.next();
+
+            match c {
+                '\\' if raw => {
+                    lex.push('\\');
+                    if let Some(c) = self.next() {
+                        lex.push(c);
+                    }
+                }
+
+                '\\' => {
+                    let offset = self.pos - 1;
+                    if let Some(esc) = self.next() {
+                        self.parse_escape(esc, offset, lex);
+                    }
+                }
+
+                _ => {
+                    let multiline = triple && matches!(c, '\n' | '\r' | '\t');
+                    if control.is_none() && !multiline {
+                        control = Some(self.pos - 1);
+                    }
+                    lex.push(c);
+                }
+            }
+        }
+
+        if end.is_none() && !self.eof {
+            self.string = Some(PendingString {
+                start,
+                quote,
+                triple,
+                raw,
+                bytes,
+                lex: lex.unwrap_or_else(|| self.input[content..self.i].to_string()),
+                control,
+            });
+            return None;
+        }
+
+        if let Some(offset) = control {
+            self.repair(
+                RepairKind::ControlCharacter,
+                offset,
+                "escaped raw control characters in string",
+            );
+        }
+
+        if end.is_none() {
+            self.repair(
+                RepairKind::UnclosedString,
+                start,
+                "closed unterminated string",
+            );
+        }
+
+        let string = match lex {
+            Some(lex) => Cow::Owned(lex),
+            None => self.slice(content, end.unwrap_or(self.i)),
+        };
+        Some(if bytes { decode_bytes(string) } else { string })
+    }
+
+    /// Appends the character escaped by `\\esc` (at `offset`) to `lex`.
+    fn parse_escape(&mut self, esc: char, offset: usize, lex: &mut String) {
+        let js = self.options.dialect.js_escapes();
+        let py = self.options.dialect.python_strings();
+
+        match esc {
+            '"' => lex.push('"'),
+            '\\' => lex.push('\\'),
+            '/' => lex.push('/'),
+            'b' => lex.push('\u{0008}'),
+            'f' => lex.push('\u{000C}'),
+            'n' => lex.push('\n'),
+            'r' => lex.push('\r'),
+            't' => lex.push('\t'),
+
+            'u' => {
+                if let Some(c) = self.parse_unicode_escape() {
+                    lex.push(c);
+                } else {
+                    lex.push(char::REPLACEMENT_CHARACTER);
+                    self.repair(
+                        RepairKind::InvalidUnicodeEscape,
+                        offset,
+                        "replaced invalid unicode escape with U+FFFD",
+                    );
+                }
+            }
+
+            '\'' if py => lex.push('\''),
+            'a' if py => lex.push('\u{0007}'),
+            'e' if self.options.dialect.ruby_literals() => lex.push('\u{001B}'),
+            'v' if js || py => lex.push('\u{000B}'),
+            '0' if js && !self.peek().is_some_and(|c| c.is_ascii_digit()) => lex.push('\0'),
+            '0'..='7' if py => {
+                // up to three octal digits
+                let mut code = esc.to_digit(8).unwrap_or(0);
+                for _ in 0..2 {
+                    match self.peek().and_then(|c| c.to_digit(8)) {
+                        Some(digit) => {
+                            code = code * 8 + digit;
+                            self.next();
+                        }
+                        None => break,
+                    }
+                }
+                lex.extend(char::from_u32(code));
+            }
+            'x' if js || py => {
+                let hex = self.input.get(self.i..self.i + 2);
+                match hex.and_then(parse_hex) {
+                    Some(code) => {
+                        lex.extend(char::from_u32(code));
+                        self.i += 2;
+                        self.pos += 2;
+                    }
+                    None => self.repair(
+                        RepairKind::InvalidEscape,
+                        offset,
+                        "dropped invalid hexadecimal escape",
+                    ),
+                }
+            }
+
+            'U' if py => {
+                let hex = self.input.get(self.i..self.i + 8);
+                match hex.and_then(parse_hex).and_then(char::from_u32) {
+                    Some(c) => {
+                        lex.push(c);
+                        self.advance(8);
+                    }
+                    None => self.repair(
+                        RepairKind::InvalidUnicodeEscape,
+                        offset,
+                        "dropped invalid unicode escape",
+                    ),
+                }
+            }
+
+            // line continuation
+            '\r' if js || py => {
+                if self.peek() == Some('\n') {
+                    self.next();
+                }
+            }
+            '\n' if js || py => {}
+            '\u{2028}' | '\u{2029}' if js => {}
+
+            ch if js && !ch.is_ascii_digit() => lex.push(ch),
+            // Python keeps the backslash of unknown escapes, e.g. in `'\d+'`
+            ch if py => {
+                lex.push('\\');
+                lex.push(ch);
+            }
+
+            ch => {
+                lex.push(ch);
+                self.repair(
+                    RepairKind::InvalidEscape,
+                    offset,
+                    format!("replaced unknown escape `\\{ch}` with {ch:?}"),
+                );
+            }
+        }
+    }
+
+    /// Reads an unquoted key: an identifier such as `$name_1`, with `\\uXXXX` escapes.
+    ///
+    /// Returns `None`, without consuming anything, if the key may go on in the next chunk.
+    fn parse_identifier(&mut self) -> Option<Cow<'a, str>> {
+        let (start, offset, diagnostics) = (self.i, self.pos, self.diagnostics.len());
+        // `lex` stays `None` while the key is a plain slice of the input from `start`
+        let mut lex: Option<String> = None;
+
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                if !self.eof && self.input.len() < self.i + 6 {
+                    self.advance(self.input.len() - self.i);
+                    break;
+                }
+
+                let escaped = self
+                    .input
+                    .get(self.i + 2..self.i + 6)
+                    .filter(|hex| {
+                        self.input[self.i..].starts_with("\\u")
+                            && hex.bytes().all(|b| b.is_ascii_hexdigit())
+                    })
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .and_then(char::from_u32);
+
+                let lex = lex.get_or_insert_with(|| self.input[start..self.i].to_string());
+                match escaped {
+                    Some(c) => {
+                        lex.push(c);
+                        self.advance(6);
+                    }
+                    None => {
+                        self.repair(
+                            RepairKind::InvalidUnicodeEscape,
+                            self.pos,
+                            "dropped invalid unicode escape",
+                        );
+                        self.next();
+                    }
+                }
+            } else if is_identifier_part(c) {
+                if let Some(lex) = &mut lex {
+                    lex.push(c);
+                }
+                self.next();
+            } else {
+                break;
+            }
+        }
+
+        if self.starved() {
+            self.i = start;
+            self.pos = offset;
+            self.diagnostics.truncate(diagnostics);
+            return None;
+        }
+
+        Some(match lex {
+            Some(lex) => Cow::Owned(lex),
+            None => self.slice(start, self.i),
+        })
+    }
+
+    /// Reads an unquoted key that isn't valid in the dialect, such as `name`, `123` or
+    /// `content-type`: everything up to whitespace or a character that ends a key.
+    ///
+    /// Returns `None`, without consuming anything, if the key may go on in the next chunk.
+    fn parse_bare_key(&mut self) -> Option<Cow<'a, str>> {
+        let (start, offset) = (self.i, self.pos);
+
+        while let Some(c) = self.peek()
+            && is_bare_key_part(c)
+        {
+            self.next();
+        }
+
+        if self.starved() {
+            self.i = start;
+            self.pos = offset;
+            return None;
+        }
+
+        Some(self.slice(start, self.i))
+    }
+
+    /// Returns `true` if `c`, the next token in an object, starts a key.
+    fn starts_key(&self, c: char) -> bool {
+        match c {
+            ',' | ')' => false,
+            ':' => self.options.dialect.ruby_literals(),
+            _ => self.at_string(c) || is_bare_key_part(c),
+        }
+    }
+
+    /// Reads a tuple key, such as `(1, 'a')`, as its text.
+    ///
+    /// Returns `None`, without consuming anything, if it may go on in the next chunk.
+    fn parse_tuple_key(&mut self) -> Option<Cow<'a, str>> {
+        let (start, offset) = (self.i, self.pos);
+
+        let (mut depth, mut quote, mut escaped) = (0, None, false);
+        while let Some(c) = self.peek() {
+            self.next();
+
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if quote.is_some() => escaped = true,
+                _ if quote == Some(c) => quote = None,
+                _ if quote.is_some() => {}
+                '\'' | '"' => quote = Some(c),
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(self.slice(start, self.i));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !self.eof {
+            self.i = start;
+            self.pos = offset;
+            return None;
+        }
+        Some(self.slice(start, self.i))
+    }
+
+    /// Reports the unquoted key at `offset`, unless the dialect allows it. Python's non-string
+    /// keys are converted the way its `json.dumps` does (`None` → `"null"`).
+    fn unquoted_key(&mut self, key: Cow<'a, str>, offset: usize) -> Cow<'a, str> {
+        if self.options.dialect.unquoted_values() {
+            return key;
+        }
+        if self.options.dialect.python_literals() {
+            match &*key {
+                "None" => return Cow::Borrowed("null"),
+                "True" => return Cow::Borrowed("true"),
+                "False" => return Cow::Borrowed("false"),
+                _ if number::is_js_decimal(&key) => return key,
+                // a tuple keeps its text, since `json.dumps` has no string for it
+                _ if key.starts_with('(') => return key,
+                _ => {}
+            }
+        }
+
+        self.repair(
+            RepairKind::UnquotedKey,
+            offset,
+            format!("quoted unquoted key {key:?}"),
+        );
+        key
+    }
+
+    /// Returns `true` if the escape sequence at the cursor is complete in the buffer,
+    /// including the low half of a `\uD83D\uDE00` surrogate pair.
+    fn escape_buffered(&self) -> bool {
+        if self.eof {
+            return true;
+        }
+
+        let js = self.options.dialect.js_escapes();
+        let py = self.options.dialect.python_strings();
+        // escapes are read by characters, which may take more than a byte
+        let rest = &self.input[self.i..];
+        let has = |len: usize| rest.chars().nth(len - 1).is_some();
+        match rest.chars().nth(1) {
+            None => false,
+
+            Some('x') if js || py => has(4),
+            Some('U') if py => has(10),
+            Some('0'..='7') if py => has(4),
+            Some('0' | '\r') if js => has(3),
+            Some('\r') if py => has(3),
+
+            Some('u') => {
+                if !has(6) {
+                    return false;
+                }
+
+                let hex = rest.chars().skip(2).take(4).collect::<String>();
+                match parse_hex(&hex) {
+                    Some(code) if (0xD800..=0xDBFF).contains(&code) => has(12),
+                    _ => true,
+                }
+            }
+
+            Some(_) => true,
+        }
+    }
+
+    /// Reads the four hex digits of a `\u` escape, and the `\uXXXX` after a high surrogate
+    /// when it is the low one. Returns `None` for a malformed escape or a lone surrogate,
+    /// having read only hex digits, so the closing quote and what follows stay in place.
+    fn parse_unicode_escape(&mut self) -> Option<char> {
+        let rest = &self.input[self.i..];
+        let len = rest
+            .bytes()
+            .take(4)
+            .take_while(u8::is_ascii_hexdigit)
+            .count();
+        let code = parse_hex(&rest[..len]).filter(|_| len == 4);
+        self.advance(len);
+
+        let code = code?;
+        if let Some(c) = char::from_u32(code) {
+            return Some(c);
+        }
+        if !(0xD800..=0xDBFF).contains(&code) {
+            return None;
+        }
+
+        let low = self.input[self.i..]
+            .strip_prefix("\\u")
+            .and_then(|rest| rest.get(..4))
+            .and_then(parse_hex)
+            .filter(|low| (0xDC00..=0xDFFF).contains(low))?;
+        self.advance(6);
+        char::from_u32(0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00))
+    }
+
+    /// Handles a `,` inside an array or object.
+    ///
+    /// `after_value` is set when the comma follows a value, and `comma` keeps the
+    /// offset of that comma so a trailing one can be reported when the container ends.
+    fn parse_comma(&mut self, after_value: &mut bool, comma: &mut Option<usize>) {
+        if *after_value {
+            *after_value = false;
+            *comma = Some(self.pos);
+        } else {
+            self.repair(RepairKind::ExtraComma, self.pos, "ignored extra comma");
+        }
+        self.next();
+    }
+
+    fn trailing_comma(&mut self, comma: Option<usize>) {
+        if let Some(offset) = comma
+            && !self.options.dialect.trailing_commas()
+        {
+            self.repair(RepairKind::ExtraComma, offset, "ignored trailing comma");
+        }
+    }
+
+    fn step_array(&mut self, mut frame: ArrayFrame<'a>) -> Parsed<'a> {
+        // a value right after another one is only missing its comma
+        if frame.skip && self.skipped.is_none() {
+            match self.peek_token() {
+                Some(c) if !matches!(c, ',' | ':' | ']' | '}' | ')') => {
+                    self.repair(RepairKind::MissingComma, self.pos, "inserted missing ','");
+                    frame.skip = false;
+                }
+                None if !self.eof => {
+                    self.stack.push(Frame::Array(frame));
+                    return Parsed::Pending;
+                }
+                _ => {}
+            }
+        }
+
+        if frame.skip {
+            if !self.skip_until(&[frame.close, ','], RepairKind::SkippedCharacters) {
+                self.stack.push(Frame::Array(frame));
+                return Parsed::Pending;
+            }
+            frame.skip = false;
+        }
+
+        let token = if frame.after_value {
+            self.peek_token()
+        } else {
+            self.peek_value()
+        };
+        match token {
+            Some(c) if c == frame.close => {
+                self.trailing_comma(frame.comma);
+                self.next();
+                Parsed::Value(frame.finish())
+            }
+
+            Some(',') => {
+                self.parse_comma(&mut frame.after_value, &mut frame.comma);
+                self.stack.push(Frame::Array(frame));
+                Parsed::Continue
+            }
+
+            Some(_) => {
+                self.stack.push(Frame::Array(frame));
+                self.parse_value()
+            }
+
+            None if self.eof => {
+                self.trailing_comma(frame.comma);
+                self.repair(
+                    RepairKind::UnclosedArray,
+                    frame.start,
+                    "closed unterminated array",
+                );
+                Parsed::Value(frame.finish())
+            }
+
+            None => {
+                self.stack.push(Frame::Array(frame));
+                Parsed::Pending
+            }
+        }
+    }
+
+    /// Skips up to the bracket closing the container, ignoring brackets in strings.
+    fn step_skip(&mut self, mut frame: SkipFrame) -> Parsed<'a> {
+        let parens = self.options.dialect.tuples() || self.options.dialect.js_values();
+        let bytes = self.input.as_bytes();
+        let mut i = self.i;
+
+        while let Some(&b) = bytes.get(i) {
+            i += 1;
+
+            if frame.escaped {
+                frame.escaped = false;
+            } else if frame.in_string {
+                match b {
+                    b'\\' => frame.escaped = true,
+                    b'"' => frame.in_string = false,
+                    _ => {}
+                }
+            } else {
+                match b {
+                    b'"' => frame.in_string = true,
+                    b'[' | b'{' => frame.depth += 1,
+                    b'(' if parens => frame.depth += 1,
+                    b']' | b'}' | b')' if b != b')' || parens => {
+                        frame.depth -= 1;
+                        if frame.depth == 0 {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        self.pos += i - self.i;
+        self.i = i;
+
+        if frame.depth == 0 || self.eof {
+            return Parsed::Value(Json::Null);
+        }
+
+        self.stack.push(Frame::Skip(frame));
+        Parsed::Pending
+    }
+
+    fn step_object(&mut self, mut frame: ObjectFrame<'a>) -> Parsed<'a> {
+        match mem::replace(&mut frame.state, ObjectState::Key) {
+            ObjectState::Key => {
+                // a key right after a value is only missing its comma
+                if frame.after_value && !self.options.dialect.space_separated() {
+                    match self.peek_token() {
+                        Some(c) if c != frame.close && self.starts_key(c) => {
+                            self.repair(RepairKind::MissingComma, self.pos, "inserted missing ','");
+                            frame.after_value = false;
+                        }
+                        None if !self.eof => {
+                            self.stack.push(Frame::Object(frame));
+                            return Parsed::Pending;
+                        }
+                        _ => {}
+                    }
+                }
+
+                match self.peek_token() {
+                    Some(c) if c == frame.close => {
+                        self.trailing_comma(frame.comma);
+                        self.next();
+                        return Parsed::Value(Json::Object(frame.obj.finish()));
+                    }
+
+                    Some(',') => {
+                        self.parse_comma(&mut frame.after_value, &mut frame.comma);
+                    }
+
+                    Some(c) if self.at_string(c) => {
+                        if self.string.is_none() {
+                            frame.key_start = self.pos;
+                        }
+                        match self.parse_string() {
+                            Some(key) => frame.state = ObjectState::Colon(key, false),
+                            None => {
+                                self.stack.push(Frame::Object(frame));
+                                return Parsed::Pending;
+                            }
+                        }
+                    }
+
+                    Some(c) if self.options.dialect.identifier_keys() && is_identifier_start(c) => {
+                        frame.key_start = self.pos;
+                        match self.parse_identifier() {
+                            Some(key) => frame.state = ObjectState::Colon(key, false),
+                            None => {
+                                self.stack.push(Frame::Object(frame));
+                                return Parsed::Pending;
+                            }
+                        }
+                    }
+
+                    Some(':') if self.options.dialect.ruby_literals() => {
+                        frame.key_start = self.pos;
+                        match self.parse_symbol() {
+                            Some(key) => frame.state = ObjectState::Colon(key, false),
+                            None => {
+                                self.stack.push(Frame::Object(frame));
+                                return Parsed::Pending;
+                            }
+                        }
+                    }
+
+                    Some('(') if self.options.dialect.tuples() => {
+                        frame.key_start = self.pos;
+                        match self.parse_tuple_key() {
+                            Some(key) => frame.state = ObjectState::Colon(key, true),
+                            None => {
+                                self.stack.push(Frame::Object(frame));
+                                return Parsed::Pending;
+                            }
+                        }
+                    }
+
+                    Some(c)
+                        if (!frame.after_value || self.options.dialect.space_separated())
+                            && is_bare_key_part(c) =>
+                    {
+                        frame.key_start = self.pos;
+                        match self.parse_bare_key() {
+                            Some(key) => frame.state = ObjectState::Colon(key, true),
+                            None => {
+                                self.stack.push(Frame::Object(frame));
+                                return Parsed::Pending;
+                            }
+                        }
+                    }
+
+                    Some(_) => {
+                        frame.state = ObjectState::Skip(if frame.after_value {
+                            RepairKind::SkippedCharacters
+                        } else {
+                            RepairKind::DroppedKey
+                        });
+                    }
+
+                    None if self.eof => {
+                        self.trailing_comma(frame.comma);
+                        self.repair(
+                            RepairKind::UnclosedObject,
+                            frame.start,
+                            "closed unterminated object",
+                        );
+                        return Parsed::Value(Json::Object(frame.obj.finish()));
+                    }
+
+                    None => {
+                        self.stack.push(Frame::Object(frame));
+                        return Parsed::Pending;
+                    }
+                }
+            }
+
+            ObjectState::Skip(kind) => {
+                if !self.skip_until(&['"', frame.close, ','], kind) {
+                    frame.state = ObjectState::Skip(kind);
+                    self.stack.push(Frame::Object(frame));
+                    return Parsed::Pending;
+                }
+                // a key after a dropped one isn't missing a comma
+                frame.after_value = kind != RepairKind::DroppedKey || self.peek() != Some('"');
+            }
+
+            ObjectState::Colon(key, unquoted) => {
+                match self.peek_token() {
+                    Some(':') => {
+                        self.next();
+                    }
+                    Some('=') => {
+                        let separator = match self.lookahead("=>") {
+                            Some(true) => "=>",
+                            Some(false) => "=",
+                            None => {
+                                frame.state = ObjectState::Colon(key, unquoted);
+                                self.stack.push(Frame::Object(frame));
+                                return Parsed::Pending;
+                            }
+                        };
+                        if separator != self.options.dialect.key_separator() {
+                            self.repair(
+                                RepairKind::MissingColon,
+                                self.pos,
+                                format!("replaced '{separator}' with ':' after key {key:?}"),
+                            );
+                        }
+                        self.advance(separator.len());
+                    }
+                    // `{'a', 'b'}` is a set, read as an array
+                    Some(',' | '}') if self.options.dialect.tuples() && frame.obj.is_empty() => {
+                        let first = if unquoted {
+                            Parser::new(key, &self.options).parse_and_fix()
+                        } else {
+                            Json::String(key)
+                        };
+                        self.stack.push(Frame::Array(ArrayFrame {
+                            start: frame.start,
+                            arr: vec![first],
+                            close: '}',
+                            call: false,
+                            after_value: true,
+                            comma: None,
+                            skip: false,
+                        }));
+                        return Parsed::Continue;
+                    }
+
+                    // not a key after all, e.g. `{1}`
+                    Some(_) if unquoted => {
+                        self.skipped = Some((frame.key_start, key.chars().count()));
+                        frame.state = ObjectState::Skip(RepairKind::DroppedKey);
+                        self.stack.push(Frame::Object(frame));
+                        return Parsed::Continue;
+                    }
+                    Some(_) => {
+                        self.repair(
+                            RepairKind::MissingColon,
+                            self.pos,
+                            format!("missing ':' after key {key:?}"),
+                        );
+                    }
+                    None if self.eof => {}
+                    None => {
+                        frame.state = ObjectState::Colon(key, unquoted);
+                        self.stack.push(Frame::Object(frame));
+                        return Parsed::Pending;
+                    }
+                }
+
+                let key = if unquoted {
+                    self.unquoted_key(key, frame.key_start)
+                } else {
+                    key
+                };
+                frame.state = ObjectState::Value(key);
+            }
+
+            ObjectState::Value(key) => {
+                frame.state = ObjectState::Value(key);
+                self.stack.push(Frame::Object(frame));
+                if self.options.dialect.space_separated() {
+                    match self.at_next_key() {
+                        Some(true) => return self.missing_value(),
+                        Some(false) => {}
+                        None => return Parsed::Pending,
+                    }
+                }
+                return self.parse_value();
+            }
+        }
+
+        self.stack.push(Frame::Object(frame));
+        Parsed::Continue
+    }
+}
+
+/// Returns `true` if `c` can start an unquoted key (a JavaScript identifier).
+fn is_identifier_start(c: char) -> bool {
+    c == '$' || c == '_' || c == '\\' || c.is_alphabetic()
+}
+
+fn is_identifier_part(c: char) -> bool {
+    c == '$' || c == '_' || c == '\u{200C}' || c == '\u{200D}' || c.is_alphanumeric()
+}
+
+/// Returns `true` if `c` can be part of an unquoted key outside the dialect's own syntax.
+fn is_bare_key_part(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, ':' | '=' | ',' | '"' | '\'' | '{' | '}' | '[' | ']')
+}
+
+/// Parses hexadecimal digits, without the sign `from_str_radix` also takes.
+fn parse_hex(hex: &str) -> Option<u32> {
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// Decodes the text of a bytes literal, where each character is a byte, as UTF-8.
+///
+/// Returns the text as is if it isn't valid UTF-8.
+fn decode_bytes(text: Cow<'_, str>) -> Cow<'_, str> {
+    if text.is_ascii() {
+        return text;
+    }
+
+    let bytes: Option<Vec<u8>> = text.chars().map(|c| u8::try_from(c).ok()).collect();
+    match bytes.map(String::from_utf8) {
+        Some(Ok(decoded)) => Cow::Owned(decoded),
+        _ => text,
+    }
+}
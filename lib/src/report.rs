@@ -0,0 +1,135 @@
+use std::fmt;
+
+/// The kind of repair applied to the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RepairKind {
+    /// A string was not terminated and was closed at the end of the input.
+    UnclosedString,
+    /// A string quoted with `'`, which the dialect doesn't allow, was quoted with `"`.
+    SingleQuotes,
+    /// An array was not terminated and was closed at the end of the input.
+    UnclosedArray,
+    /// An object was not terminated and was closed at the end of the input.
+    UnclosedObject,
+    /// A comment the dialect doesn't allow (`//` and `/* */` outside JSON5, `#` outside Python)
+    /// was removed.
+    Comment,
+    /// A block comment was not terminated and was closed at the end of the input.
+    UnclosedComment,
+    /// Whitespace the dialect doesn't allow, such as a byte order mark or a no-break space,
+    /// was removed.
+    Whitespace,
+    /// An unknown escape sequence (e.g. `\x`) was replaced by the escaped character.
+    InvalidEscape,
+    /// A malformed `\u` escape or lone surrogate was replaced by U+FFFD.
+    InvalidUnicodeEscape,
+    /// A raw control character inside a string was escaped.
+    ControlCharacter,
+    /// A malformed number was normalized (or kept as a string).
+    NumberRepaired,
+    /// A misspelled, truncated or wrongly cased `true`/`false`/`null` was normalized.
+    LiteralRepaired,
+    /// A bare word in value position, such as `ok` in `{"status": ok}`, was quoted or dropped
+    /// (see [`FixOptions::bare_words`](crate::FixOptions::bare_words)).
+    BareWord,
+    /// An unexpected character in value position was replaced by `null`.
+    InvalidValue,
+    /// A value was missing, at the end of the input or before the `,` or `}` ending its
+    /// member, and `null` was used instead.
+    MissingValue,
+    /// The `:` between an object key and its value was missing.
+    MissingColon,
+    /// The `,` between two array elements or object members was missing.
+    MissingComma,
+    /// A leading, repeated or trailing comma was ignored.
+    ExtraComma,
+    /// An unquoted object key, such as `name` in `{name: 1}`, was quoted.
+    UnquotedKey,
+    /// A non-string object key was dropped together with its value.
+    DroppedKey,
+    /// Unexpected characters between values were skipped.
+    SkippedCharacters,
+    /// Characters after the root value were ignored.
+    TrailingCharacters,
+    /// A container nested deeper than [`FixOptions::max_depth`](crate::FixOptions::max_depth)
+    /// was replaced by `null`.
+    TooDeep,
+    /// An object key was repeated, and handled according to
+    /// [`FixOptions::duplicate_keys`](crate::FixOptions::duplicate_keys).
+    DuplicateKey,
+}
+
+/// A single repair applied to the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: RepairKind,
+    /// Byte offset in the original input.
+    pub offset: usize,
+    /// 1-based line in the original input.
+    pub line: usize,
+    /// 1-based column (in characters) in the original input.
+    pub column: usize,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(kind: RepairKind, offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            offset,
+            line: 0,
+            column: 0,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// The output of [`fix_with_report`](crate::fix_with_report).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// The fixed JSON, same as [`fix_with`](crate::fix_with).
+    pub output: String,
+    /// Every repair applied to the input, ordered by offset.
+    ///
+    /// Repairs inside nested JSON strings are not reported.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Report {
+    /// Returns `true` if the input needed no repairs.
+    pub fn is_clean(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+/// Sorts the diagnostics by offset and fills in their line and column.
+pub(crate) fn locate(input: &str, diagnostics: &mut [Diagnostic]) {
+    diagnostics.sort_by_key(|d| d.offset);
+
+    let (mut line, mut column) = (1, 1);
+    let mut chars = input.char_indices().peekable();
+
+    for diagnostic in diagnostics {
+        while let Some(&(i, c)) = chars.peek()
+            && i < diagnostic.offset
+        {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+            chars.next();
+        }
+
+        diagnostic.line = line;
+        diagnostic.column = column;
+    }
+}
@@ -0,0 +1,298 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+
+use ::serde::de::value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer};
+use ::serde::de::{
+    self, Deserialize, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use ::serde::forward_to_deserialize_any;
+use ::serde::ser::{Serialize, Serializer};
+
+use crate::{Diagnostic, Json, Map};
+
+/// The error returned by [`fix_to`](crate::fix_to) when the repaired value
+/// doesn't match the target type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeserializeError {
+    /// What didn't match (e.g. `"missing field `id`"`).
+    pub message: String,
+    /// Every repair applied to the input, as in [`Report`](crate::Report).
+    ///
+    /// Empty when the error comes from deserializing a [`Json`] directly.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for DeserializeError {}
+
+impl de::Error for DeserializeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+impl Serialize for Json<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Json::Null => serializer.serialize_unit(),
+            Json::True => serializer.serialize_bool(true),
+            Json::False => serializer.serialize_bool(false),
+
+            Json::Number(val) => {
+                if let Ok(n) = val.parse::<u64>() {
+                    serializer.serialize_u64(n)
+                } else if let Ok(n) = val.parse::<i64>() {
+                    serializer.serialize_i64(n)
+                } else if let Ok(n) = val.parse::<u128>() {
+                    serializer.serialize_u128(n)
+                } else if let Ok(n) = val.parse::<i128>() {
+                    serializer.serialize_i128(n)
+                } else if let Ok(n) = val.parse::<f64>() {
+                    serializer.serialize_f64(n)
+                } else {
+                    serializer.serialize_str(val)
+                }
+            }
+
+            Json::String(val) => serializer.serialize_str(val),
+            Json::Array(arr) => serializer.collect_seq(arr),
+            Json::Object(obj) => serializer.collect_map(obj),
+        }
+    }
+}
+
+/// Deserializes any self-describing format into a value, borrowing strings when the format allows it.
+impl<'de: 'a, 'a> Deserialize<'de> for Json<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonVisitor(PhantomData))
+    }
+}
+
+struct JsonVisitor<'a>(PhantomData<Json<'a>>);
+
+impl<'de: 'a, 'a> Visitor<'de> for JsonVisitor<'a> {
+    type Value = Json<'a>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        Json::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, val: bool) -> Result<Self::Value, E> {
+        Ok(if val { Json::True } else { Json::False })
+    }
+
+    fn visit_i64<E: de::Error>(self, val: i64) -> Result<Self::Value, E> {
+        Ok(Json::Number(Cow::Owned(val.to_string())))
+    }
+
+    fn visit_u64<E: de::Error>(self, val: u64) -> Result<Self::Value, E> {
+        Ok(Json::Number(Cow::Owned(val.to_string())))
+    }
+
+    fn visit_i128<E: de::Error>(self, val: i128) -> Result<Self::Value, E> {
+        Ok(Json::Number(Cow::Owned(val.to_string())))
+    }
+
+    fn visit_u128<E: de::Error>(self, val: u128) -> Result<Self::Value, E> {
+        Ok(Json::Number(Cow::Owned(val.to_string())))
+    }
+
+    /// NaN and infinities have no JSON representation and become `null`.
+    fn visit_f64<E: de::Error>(self, val: f64) -> Result<Self::Value, E> {
+        if val.is_finite() {
+            Ok(Json::Number(Cow::Owned(val.to_string())))
+        } else {
+            Ok(Json::Null)
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+        Ok(Json::String(Cow::Owned(val.to_string())))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, val: &'de str) -> Result<Self::Value, E> {
+        Ok(Json::String(Cow::Borrowed(val)))
+    }
+
+    fn visit_string<E: de::Error>(self, val: String) -> Result<Self::Value, E> {
+        Ok(Json::String(Cow::Owned(val)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut arr = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(val) = seq.next_element()? {
+            arr.push(val);
+        }
+        Ok(Json::Array(arr))
+    }
+
+    /// Duplicate keys keep the last value, like [`parse_strict`](crate::parse_strict).
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut obj = Map::new();
+        while let Some((Key(key), val)) = map.next_entry()? {
+            obj.insert_last(key, val);
+        }
+        Ok(Json::Object(obj))
+    }
+}
+
+/// An object key that borrows from the input when the format allows it.
+struct Key<'a>(Cow<'a, str>);
+
+impl<'de: 'a, 'a> Deserialize<'de> for Key<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(KeyVisitor(PhantomData))
+    }
+}
+
+struct KeyVisitor<'a>(PhantomData<Key<'a>>);
+
+impl<'de: 'a, 'a> Visitor<'de> for KeyVisitor<'a> {
+    type Value = Key<'a>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string key")
+    }
+
+    fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+        Ok(Key(Cow::Owned(val.to_string())))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, val: &'de str) -> Result<Self::Value, E> {
+        Ok(Key(Cow::Borrowed(val)))
+    }
+
+    fn visit_string<E: de::Error>(self, val: String) -> Result<Self::Value, E> {
+        Ok(Key(Cow::Owned(val)))
+    }
+}
+
+fn visit_number<'de, V: Visitor<'de>>(val: &str, visitor: V) -> Result<V::Value, DeserializeError> {
+    if let Ok(n) = val.parse::<u64>() {
+        visitor.visit_u64(n)
+    } else if let Ok(n) = val.parse::<i64>() {
+        visitor.visit_i64(n)
+    } else if let Ok(n) = val.parse::<u128>() {
+        visitor.visit_u128(n)
+    } else if let Ok(n) = val.parse::<i128>() {
+        visitor.visit_i128(n)
+    } else if let Ok(n) = val.parse::<f64>() {
+        visitor.visit_f64(n)
+    } else {
+        visitor.visit_str(val)
+    }
+}
+
+/// Deserializes a user type straight from a repaired value, without printing and re-parsing it.
+impl<'de> Deserializer<'de> for Json<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Json::Null => visitor.visit_unit(),
+            Json::True => visitor.visit_bool(true),
+            Json::False => visitor.visit_bool(false),
+            Json::Number(val) => visit_number(&val, visitor),
+            Json::String(Cow::Borrowed(val)) => visitor.visit_borrowed_str(val),
+            Json::String(Cow::Owned(val)) => visitor.visit_string(val),
+
+            Json::Array(arr) => {
+                let mut seq = SeqDeserializer::new(arr.into_iter());
+                let val = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(val)
+            }
+
+            Json::Object(obj) => {
+                let mut map = MapDeserializer::new(obj.into_iter());
+                let val = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(val)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Json::Null => visitor.visit_none(),
+            other => visitor.visit_some(other),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Unit variants are strings (`"Red"`), other variants single-key objects (`{"Rgb": [0, 0, 0]}`).
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self {
+            Json::String(val) => visitor.visit_enum(val.into_deserializer()),
+            Json::Object(obj) if obj.len() == 1 => {
+                let entries = obj.into_iter();
+                visitor.visit_enum(MapAccessDeserializer::new(MapDeserializer::new(entries)))
+            }
+            other => Err(de::Error::invalid_type(
+                other.unexpected(),
+                &"a string or a single-key object",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeserializeError> for Json<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl Json<'_> {
+    fn unexpected(&self) -> de::Unexpected<'_> {
+        match self {
+            Json::Null => de::Unexpected::Unit,
+            Json::True => de::Unexpected::Bool(true),
+            Json::False => de::Unexpected::Bool(false),
+            Json::Number(val) => de::Unexpected::Other(val),
+            Json::String(val) => de::Unexpected::Str(val),
+            Json::Array(_) => de::Unexpected::Seq,
+            Json::Object(_) => de::Unexpected::Map,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::borrow::Cow;
+
+use crate::parser::{Parser, Snapshot};
+use crate::{FixOptions, Json};
+
+/// Incremental parser for JSON that arrives in chunks (e.g. LLM or SSE responses).
+///
+/// Each [`feed`](Self::feed) only parses the new input: completed values are kept
+/// and only an unfinished literal or number at the end of a chunk is read again.
+/// Likewise, [`value`](Self::value) only copies what was completed since its last call.
+///
+/// ```
+/// use fjson_core::StreamParser;
+///
+/// let mut stream = StreamParser::new();
+///
+/// stream.feed(r#"{"status": "run"#);
+/// assert_eq!(stream.value().get("status").and_then(|v| v.as_str()), Some("run"));
+///
+/// stream.feed(r#"ning", "items": [1, 2"#);
+/// assert_eq!(stream.value().get("status").and_then(|v| v.as_str()), Some("running"));
+///
+/// stream.feed("]}");
+/// assert_eq!(stream.finish(), fjson_core::fix_value(r#"{"status": "running", "items": [1, 2]}"#));
+/// ```
+#[derive(Clone)]
+pub struct StreamParser {
+    parser: Parser<'static, 'static>,
+    snapshot: Snapshot<'static>,
+}
+
+impl Default for StreamParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamParser {
+    pub fn new() -> Self {
+        Self::with_options(FixOptions::default())
+    }
+
+    pub fn with_options(options: FixOptions) -> Self {
+        Self {
+            parser: Parser::streaming(Cow::Owned(options)),
+            snapshot: Snapshot::default(),
+        }
+    }
+
+    /// Appends a chunk of input.
+    pub fn feed(&mut self, chunk: &str) {
+        self.parser.feed(chunk);
+    }
+
+    /// Returns the repaired value for the input fed so far, as if it ended here.
+    ///
+    /// Same result as [`fix_value_with`](crate::fix_value_with) on the whole input so far.
+    /// Calling it after each chunk is cheap: the value is kept between calls, and only the
+    /// parts that changed are updated.
+    pub fn value(&mut self) -> &Json<'static> {
+        self.parser.snapshot(&mut self.snapshot)
+    }
+
+    /// Ends the input and returns the repaired value.
+    pub fn finish(mut self) -> Json<'static> {
+        let json = self.parser.parse_and_fix();
+        crate::deserialize(json, self.parser.options())
+    }
+}
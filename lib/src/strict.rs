@@ -0,0 +1,376 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use crate::map::MapBuilder;
+use crate::{DuplicateKeys, Json};
+
+/// Containers nested deeper than this are rejected instead of overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+/// The first error found by [`parse_strict`](crate::parse_strict).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset in the input.
+    pub offset: usize,
+    /// 1-based line in the input.
+    pub line: usize,
+    /// 1-based column (in characters) in the input.
+    pub column: usize,
+    /// What the parser expected at this position (e.g. `"',' or ']'"`).
+    pub expected: String,
+    /// What it found instead (e.g. `"'x'"` or `"end of input"`).
+    pub found: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, found {}",
+            self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// RFC 8259 parser that rejects anything [`Parser`](crate::parser::Parser) would repair.
+pub(crate) struct StrictParser<'a> {
+    input: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+    duplicate_keys: DuplicateKeys,
+}
+
+impl<'a> StrictParser<'a> {
+    pub fn new(input: &'a str, duplicate_keys: DuplicateKeys) -> Self {
+        Self {
+            input,
+            bytes: input.as_bytes(),
+            pos: 0,
+            depth: 0,
+            duplicate_keys,
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<Json<'a>, ParseError> {
+        let json = self.parse_value()?;
+
+        self.skip_whitespace();
+        if self.pos < self.bytes.len() {
+            return Err(self.error("end of input"));
+        }
+
+        Ok(json)
+    }
+
+    fn error(&self, expected: &str) -> ParseError {
+        let found = match self.input[self.pos..].chars().next() {
+            Some(c) => format!("{c:?}"),
+            None => String::from("end of input"),
+        };
+        self.error_found(self.pos, expected, found)
+    }
+
+    fn error_found(&self, offset: usize, expected: &str, found: String) -> ParseError {
+        let (mut line, mut column) = (1, 1);
+        for c in self.input[..offset].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+
+        ParseError {
+            offset,
+            line,
+            column,
+            expected: expected.to_string(),
+            found,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8, expected: &str) -> Result<(), ParseError> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(expected))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json<'a>, ParseError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(b'n') => self.parse_literal("null", Json::Null),
+            Some(b't') => self.parse_literal("true", Json::True),
+            Some(b'f') => self.parse_literal("false", Json::False),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(b'"') => self.parse_string().map(Json::String),
+            Some(b'[') => self.parse_array(),
+            Some(b'{') => self.parse_object(),
+            _ => Err(self.error("a JSON value")),
+        }
+    }
+
+    fn parse_literal(&mut self, keyword: &str, json: Json<'a>) -> Result<Json<'a>, ParseError> {
+        for expected in keyword.bytes() {
+            if self.peek() != Some(expected) {
+                return Err(self.error(&format!("`{keyword}`")));
+            }
+            self.pos += 1;
+        }
+
+        Ok(json)
+    }
+
+    fn parse_digits(&mut self) -> Result<(), ParseError> {
+        if !matches!(self.peek(), Some(b'0'..=b'9')) {
+            return Err(self.error("a digit"));
+        }
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    fn parse_number(&mut self) -> Result<Json<'a>, ParseError> {
+        let start = self.pos;
+
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+
+        // integer
+        if self.peek() == Some(b'0') {
+            self.pos += 1;
+        } else {
+            self.parse_digits()?;
+        }
+
+        // float
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            self.parse_digits()?;
+        }
+
+        // exponent
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            self.parse_digits()?;
+        }
+
+        Ok(Json::Number(Cow::Borrowed(&self.input[start..self.pos])))
+    }
+
+    /// Borrows the contents from the input unless they contain escapes.
+    fn parse_string(&mut self) -> Result<Cow<'a, str>, ParseError> {
+        let mut lex: Option<String> = None;
+
+        self.pos += 1;
+        let content = self.pos;
+        loop {
+            let start = self.pos;
+            while let Some(b) = self.peek()
+                && b != b'"'
+                && b != b'\\'
+                && b >= 0x20
+            {
+                self.pos += 1;
+            }
+            if let Some(lex) = &mut lex {
+                lex.push_str(&self.input[start..self.pos]);
+            }
+
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(match lex {
+                        Some(lex) => Cow::Owned(lex),
+                        None => Cow::Borrowed(&self.input[content..self.pos - 1]),
+                    });
+                }
+
+                Some(b'\\') => {
+                    let lex = lex.get_or_insert_with(|| self.input[content..self.pos].to_string());
+                    self.pos += 1;
+                    let esc = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{0008}',
+                        Some(b'f') => '\u{000C}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            lex.push(self.parse_unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.error("an escape character")),
+                    };
+                    lex.push(esc);
+                    self.pos += 1;
+                }
+
+                Some(_) => return Err(self.error("'\"' or an escaped control character")),
+
+                None => return Err(self.error("'\"'")),
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .peek()
+                .and_then(|b| (b as char).to_digit(16))
+                .ok_or_else(|| self.error("a hex digit"))?;
+            code = code * 16 + digit;
+            self.pos += 1;
+        }
+        Ok(code)
+    }
+
+    /// Parses the `uXXXX` part of an escape, including a following low surrogate.
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let start = self.pos - 1;
+        self.pos += 1;
+        let code = self.parse_hex4()?;
+
+        let lone_surrogate = |p: &Self| {
+            p.error_found(
+                start,
+                "a valid unicode escape",
+                String::from("lone surrogate"),
+            )
+        };
+
+        if let Some(ch) = char::from_u32(code) {
+            return Ok(ch);
+        }
+
+        if !(0xD800..=0xDBFF).contains(&code)
+            || self.peek() != Some(b'\\')
+            || self.bytes.get(self.pos + 1) != Some(&b'u')
+        {
+            return Err(lone_surrogate(self));
+        }
+
+        self.pos += 2;
+        let low_code = self.parse_hex4()?;
+
+        if !(0xDC00..=0xDFFF).contains(&low_code) {
+            return Err(lone_surrogate(self));
+        }
+
+        let full_code = 0x10000 + ((code - 0xD800) << 10) + (low_code - 0xDC00);
+        char::from_u32(full_code).ok_or_else(|| lone_surrogate(self))
+    }
+
+    fn enter(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error_found(
+                self.pos,
+                &format!("at most {MAX_DEPTH} levels of nesting"),
+                String::from("deeper nesting"),
+            ));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn parse_array(&mut self) -> Result<Json<'a>, ParseError> {
+        let mut arr = Vec::new();
+
+        self.enter()?;
+        self.skip_whitespace();
+
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            self.depth -= 1;
+            return Ok(Json::Array(arr));
+        }
+
+        loop {
+            arr.push(self.parse_value()?);
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => break,
+                _ => return Err(self.error("',' or ']'")),
+            }
+        }
+
+        self.pos += 1;
+        self.depth -= 1;
+        Ok(Json::Array(arr))
+    }
+
+    fn parse_object(&mut self) -> Result<Json<'a>, ParseError> {
+        let mut obj = MapBuilder::default();
+
+        self.enter()?;
+        self.skip_whitespace();
+
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            self.depth -= 1;
+            return Ok(Json::Object(obj.finish()));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("a string key"));
+            }
+            let key_start = self.pos;
+            let key = self.parse_string()?;
+            if self.duplicate_keys == DuplicateKeys::Error && obj.contains_key(&key) {
+                return Err(self.error_found(
+                    key_start,
+                    "a unique key",
+                    format!("duplicate key {key:?}"),
+                ));
+            }
+
+            self.skip_whitespace();
+            self.expect(b':', "':'")?;
+
+            let val = self.parse_value()?;
+            obj.push(key, val, self.duplicate_keys);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => break,
+                _ => return Err(self.error("',' or '}'")),
+            }
+        }
+
+        self.pos += 1;
+        self.depth -= 1;
+        Ok(Json::Object(obj.finish()))
+    }
+}
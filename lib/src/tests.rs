@@ -1,4 +1,10 @@
-use crate::fix;
+use crate::{
+    BareWords, Decimal, Dialect, DuplicateKeys, FixOptions, Json, LineEnding, Map, NonFinite,
+    NumberError, NumberFormat, RepairKind, StreamParser, Undefined, fix, fix_reader,
+    fix_reader_to_writer, fix_reader_with, fix_to_fmt, fix_to_writer, fix_value,
+    fix_value_borrowed, fix_value_borrowed_with, fix_value_with, fix_with, fix_with_report,
+    parse_strict, parse_strict_with,
+};
 
 #[test]
 fn test_static_values() {
@@ -14,13 +20,105 @@ fn test_static_values() {
     assert_eq!(String::from("false"), fix("FALSE"));
     assert_eq!(String::from("null"), fix("NuLl"));
 
-    assert_eq!(String::from("true"), fix("truefalse01"));
+    assert_eq!(String::from("\"truefalse01\""), fix("truefalse01"));
 
     assert_eq!(String::from("true"), fix("  true  "));
     assert_eq!(String::from("false"), fix("\nfalse\t"));
     assert_eq!(String::from("null"), fix("null "));
 }
 
+#[test]
+fn test_bare_words() {
+    assert_eq!(String::from("\"foo\""), fix("foo"));
+    assert_eq!(String::from("\"nope\""), fix("nope"));
+    assert_eq!(String::from("\"tomato\""), fix("tomato"));
+    assert_eq!(String::from("true"), fix("t"));
+    assert_eq!(String::from("null"), fix("None"));
+    assert_eq!(String::from("null"), fix("nil"));
+
+    assert_eq!(
+        String::from(r#"["foo",true,"nope",false,null]"#),
+        fix_with(
+            "[foo, TRU, nope, f, undefined]",
+            &FixOptions::new().minify(true)
+        )
+    );
+    assert_eq!(
+        String::from(r#"{"status":"ok","a":null}"#),
+        fix_with(
+            r#"{"status": ok, "a": Nul}"#,
+            &FixOptions::new().minify(true)
+        )
+    );
+
+    // bare words run up to whitespace or a structural character
+    assert_eq!(
+        String::from(r#"{"w":"10px","at":"2024-01-01","t":"12:30","url":"http://x.io/a?b=1"}"#),
+        fix_with(
+            "{\"w\": 10px, \"at\": 2024-01-01, \"t\": 12:30, \"url\": http://x.io/a?b=1}",
+            &FixOptions::new().minify(true)
+        )
+    );
+    assert_eq!(
+        String::from(r#"[1,"abc",2,"pending",[true]]"#),
+        fix_with("[1 abc 2 pending [t]]", &FixOptions::new().minify(true))
+    );
+
+    // a span in single quotes is one string, if it ends before a delimiter on the same line
+    let minified = FixOptions::new().minify(true);
+    for (input, expected) in [
+        ("['hello world']", r#"["hello world"]"#),
+        (r#"{"a": 'x y'}"#, r#"{"a":"x y"}"#),
+        (
+            r#"{'b c': 'it\'s "ok"', 'd': 1}"#,
+            r#"{"b c":"it's \"ok\"","d":1}"#,
+        ),
+        ("[it's, 'twas\n]", r#"["it's","'twas"]"#),
+        (r#"{"msg": 'don't do that'}"#, r#"{"msg":"don't do that"}"#),
+        ("['rock 'n' roll' , 1]", r#"["rock 'n' roll",1]"#),
+    ] {
+        assert_eq!(fix_with(input, &minified), expected, "{input:?}");
+
+        let mut stream = StreamParser::with_options(minified.clone());
+        for c in input.chars() {
+            stream.feed(c.encode_utf8(&mut [0; 4]));
+        }
+        assert_eq!(
+            stream.finish(),
+            fix_value_with(input, &minified),
+            "{input:?}"
+        );
+    }
+
+    let options = FixOptions::new().minify(true).bare_words(BareWords::Drop);
+    assert_eq!(String::from("[1,2]"), fix_with("[1 abc 2]", &options));
+    assert_eq!(
+        String::from("[1,true,2]"),
+        fix_with("[1, foo, tru, bar_2, 2]", &options)
+    );
+    assert_eq!(
+        String::from(r#"{"b":false}"#),
+        fix_with(r#"{"a": tomato, "b": fals}"#, &options)
+    );
+    assert_eq!(String::from("null"), fix_with("nope", &options));
+
+    let report = fix_with_report("[nope, nul]", &options);
+    assert_eq!(report.output, "[null]");
+    let kinds: Vec<_> = report.diagnostics.iter().map(|d| d.kind).collect();
+    assert_eq!(kinds, [RepairKind::BareWord, RepairKind::LiteralRepaired]);
+    assert_eq!(report.diagnostics[0].message, "dropped bare word \"nope\"");
+
+    // words split across chunks are only read once complete
+    let input = "[nope 10px, tru, {\"a\": 2024-01-01, \"b\": nul}]";
+    for options in [FixOptions::new(), options] {
+        let mut stream = StreamParser::with_options(options.clone());
+        for c in input.chars() {
+            stream.feed(c.encode_utf8(&mut [0; 4]));
+        }
+        assert_eq!(stream.finish(), fix_value_with(input, &options));
+    }
+}
+
 #[test]
 fn test_numbers() {
     assert_eq!(String::from("0"), fix("0"));
@@ -51,13 +149,13 @@ fn test_numbers() {
     assert_eq!(String::from("-1"), fix("--1"));
     assert_eq!(String::from("-5"), fix("---5"));
 
-    assert_eq!(String::from("1.2"), fix("1.2.3"));
+    assert_eq!(String::from("\"1.2.3\""), fix("1.2.3"));
     assert_eq!(String::from("-0.5"), fix("-.5"));
 
     assert_eq!(String::from("-0"), fix("-"));
 
     assert_eq!(String::from("1"), fix("1e+"));
-    assert_eq!(String::from("1e2"), fix("1e2e3"));
+    assert_eq!(String::from("\"1e2e3\""), fix("1e2e3"));
 }
 
 #[test]
@@ -117,7 +215,10 @@ fn test_arrays() {
     assert_eq!(String::from("[\n   1,\n   2\n]"), fix("[1,,,2]"));
     assert_eq!(String::from("[]"), fix("[,,,]"));
 
-    assert_eq!(String::from("[\n   1\n]"), fix("[1 abc 2]"));
+    assert_eq!(
+        String::from("[\n   1,\n   \"abc\",\n   2\n]"),
+        fix("[1 abc 2]")
+    );
     assert_eq!(String::from("[\n   1,\n   2\n]"), fix("[   1   ,   2   ]"));
 
     assert_eq!(String::from("[\n   [\n      []\n   ]\n]"), fix("[[[]]]"));
@@ -185,12 +286,13 @@ fn test_objects() {
     );
 
     assert_eq!(
-        String::from("{\n   \"key\": null\n}"),
+        String::from("{\n   \"key\": \"value\"\n}"),
         fix("{\"key\" value}")
     );
-    assert_eq!(String::from("{}"), fix("{123: 1}"));
-    assert_eq!(String::from("{}"), fix("{true: 1}"));
-    assert_eq!(String::from("{}"), fix("{null: 2}"));
+    assert_eq!(String::from("{\n   \"123\": 1\n}"), fix("{123: 1}"));
+    assert_eq!(String::from("{\n   \"true\": 1\n}"), fix("{true: 1}"));
+    assert_eq!(String::from("{\n   \"null\": 2\n}"), fix("{null = 2}"));
+    assert_eq!(String::from("{}"), fix("{[1]: 2}"));
 
     assert_eq!(String::from("{\n   \"\": 1\n}"), fix("{\"\": 1}"));
     assert_eq!(String::from("{\n   \"\": 1\n}"), fix("{\"\": 1,"));
@@ -203,21 +305,53 @@ fn test_objects() {
 
 #[test]
 fn test_string_edge_cases() {
-    assert_eq!(String::from("\"\""), fix(r#""\u1""#));
-    assert_eq!(String::from("\"\""), fix(r#""\u12""#));
-    assert_eq!(String::from("\"\""), fix(r#""\u123""#));
-    assert_eq!(String::from("\"\""), fix(r#""\uXXXX""#));
-    assert_eq!(String::from("\"\""), fix(r#""\uGHIJ""#));
-    assert_eq!(String::from("\"\""), fix(r#""\u12XY""#));
-    assert_eq!(String::from("\"\""), fix(r#""\u---1""#));
-
-    assert_eq!(String::from("\"\""), fix(r#""\uD800""#));
-    assert_eq!(String::from("\"\""), fix(r#""\uDC00""#));
-    assert_eq!(String::from("\"\""), fix(r#""\uDBFF""#));
-    assert_eq!(String::from("\"\""), fix(r#""\uDFFF""#));
-
-    assert_eq!(String::from("\"\""), fix(r#""\uD800\u0041""#));
-    assert_eq!(String::from("\"\""), fix(r#""\uD800\u0030""#));
+    // malformed escapes become U+FFFD, and only their hex digits are read
+    assert_eq!(String::from("\"\u{fffd}\""), fix(r#""\u1""#));
+    assert_eq!(String::from("\"\u{fffd}\""), fix(r#""\u12""#));
+    assert_eq!(String::from("\"\u{fffd}\""), fix(r#""\u123""#));
+    assert_eq!(String::from("\"\u{fffd}XXXX\""), fix(r#""\uXXXX""#));
+    assert_eq!(String::from("\"\u{fffd}GHIJ\""), fix(r#""\uGHIJ""#));
+    assert_eq!(String::from("\"\u{fffd}XY\""), fix(r#""\u12XY""#));
+    assert_eq!(String::from("\"\u{fffd}---1\""), fix(r#""\u---1""#));
+
+    assert_eq!(String::from("\"\u{fffd}\""), fix(r#""\uD800""#));
+    assert_eq!(String::from("\"\u{fffd}\""), fix(r#""\uDC00""#));
+    assert_eq!(String::from("\"\u{fffd}\""), fix(r#""\uDBFF""#));
+    assert_eq!(String::from("\"\u{fffd}\""), fix(r#""\uDFFF""#));
+
+    // the escape after a high surrogate is only read with it if it is the low one
+    assert_eq!(String::from("\"\u{fffd}A\""), fix(r#""\uD800\u0041""#));
+    assert_eq!(String::from("\"\u{fffd}0\""), fix(r#""\uD800\u0030""#));
+    assert_eq!(
+        String::from("\"\u{fffd}\u{fffd}\""),
+        fix(r#""\uD800\uD800""#)
+    );
+
+    // the closing quote and the members after it are kept
+    let minified = FixOptions::new().minify(true);
+    for (input, expected) in [
+        (r#"{"a": "\ud83d", "b": 1}"#, "{\"a\":\"\u{fffd}\",\"b\":1}"),
+        (r#"["\u12", "x"]"#, "[\"\u{fffd}\",\"x\"]"),
+        (
+            r#"{"a": "\uzz", "b": [2]}"#,
+            "{\"a\":\"\u{fffd}zz\",\"b\":[2]}",
+        ),
+        (
+            r#"{"a": "\ud83d\u", "b": 1}"#,
+            "{\"a\":\"\u{fffd}\u{fffd}\",\"b\":1}",
+        ),
+    ] {
+        let report = fix_with_report(input, &minified);
+        assert_eq!(report.output, expected, "{input}");
+        assert!(
+            report
+                .diagnostics
+                .iter()
+                .all(|d| d.kind == RepairKind::InvalidUnicodeEscape),
+            "{input}: {:?}",
+            report.diagnostics
+        );
+    }
 
     assert_eq!(String::from(r#""\u0000""#), fix(r#""\u0000""#));
     assert_eq!(String::from("\"\u{ffff}\""), fix(r#""\uFFFF""#));
@@ -225,7 +359,10 @@ fn test_string_edge_cases() {
     assert_eq!(String::from("\"\""), fix(r#""\uE000""#));
 
     assert_eq!(String::from(r#""testAB""#), fix(r#""test\u0041\u0042""#));
-    assert_eq!(String::from(r#""abAc""#), fix(r#""a\uXXXXb\u0041c""#));
+    assert_eq!(
+        String::from("\"a\u{fffd}XXXXbAc\""),
+        fix(r#""a\uXXXXb\u0041c""#)
+    );
 }
 
 #[test]
@@ -265,8 +402,7 @@ fn test_deserialize() {
    "a": {
       "b": null
    },
-   "c\": 1}": null,
-   "}": null
+   "c\": 1}": null
 }"#
     );
 
@@ -327,3 +463,1794 @@ fn test_empty() {
 
     assert_eq!(String::from("1e999"), fix("1e999"));
 }
+
+#[test]
+fn test_value() {
+    let json = fix_value(
+        r#"{"name": "bob", "ok": tru, "tags": ["a", 2], "z": null, "nested": "{\"k\": 1}""#,
+    );
+
+    assert_eq!(json.get("name").and_then(Json::as_str), Some("bob"));
+    assert_eq!(json.get("ok").and_then(Json::as_bool), Some(true));
+    assert!(json.get("z").is_some_and(Json::is_null));
+    assert!(json.get("missing").is_none());
+
+    let tags = json.get("tags").and_then(Json::as_array).unwrap();
+    assert_eq!(tags[0].as_str(), Some("a"));
+    assert_eq!(tags[1].as_number(), Some("2"));
+    assert_eq!(tags[1].as_str(), None);
+
+    assert_eq!(
+        json.get("nested")
+            .and_then(|v| v.get("k"))
+            .and_then(Json::as_number),
+        Some("1")
+    );
+
+    let keys = json.entries().unwrap().map(|(k, _)| k).collect::<Vec<_>>();
+    assert_eq!(keys, ["name", "ok", "tags", "z", "nested"]);
+
+    let dup = fix_value(r#"{"b": 1, "a": 2, "b": 3}"#);
+    let keys = dup
+        .entries()
+        .unwrap()
+        .map(|(k, v)| (k, v.as_number().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(keys, [("a", "2"), ("b", "3")]);
+
+    assert!(fix_value("[1]").entries().is_none());
+    assert!(fix_value("[1]").get("0").is_none());
+
+    assert_eq!(
+        json.to_string(),
+        fix(r#"{"name": "bob", "ok": tru, "tags": ["a", 2], "z": null, "nested": "{\"k\": 1}""#)
+    );
+}
+
+#[test]
+fn test_options() {
+    let options = FixOptions::new().indent("  ");
+    assert_eq!(
+        fix_with("{\"a\": [1, {\"b\": 2}]}", &options),
+        "{\n  \"a\": [\n    1,\n    {\n      \"b\": 2\n    }\n  ]\n}"
+    );
+    assert_eq!(
+        fix_with("[1]", &FixOptions::new().indent("\t")),
+        "[\n\t1\n]"
+    );
+
+    let options = FixOptions::new().deserialize_nested(false);
+    assert_eq!(
+        fix_with(r#"{"data": "{\"nested\": true}"}"#, &options),
+        "{\n   \"data\": \"{\\\"nested\\\": true}\"\n}"
+    );
+    assert_eq!(fix_value_with(r#""[1]""#, &options).as_str(), Some("[1]"));
+
+    let options = FixOptions::new().normalize_numbers(false);
+    assert_eq!(fix_with("007", &options), "\"007\"");
+    assert_eq!(fix_with("1.", &options), "\"1.\"");
+    assert_eq!(fix_with("-", &options), "\"-\"");
+    assert_eq!(fix_with("1e+", &options), "\"1e+\"");
+    assert_eq!(
+        fix_with("[0, -1.5, 2e10, .5]", &options),
+        "[\n   0,\n   -1.5,\n   2e10,\n   \".5\"\n]"
+    );
+
+    let options = FixOptions::new().duplicate_keys(DuplicateKeys::FirstWins);
+    assert_eq!(
+        fix_with(r#"{"a": 1, "b": 2, "a": 3}"#, &options),
+        "{\n   \"a\": 1,\n   \"b\": 2\n}"
+    );
+    assert_eq!(
+        fix_with(r#"{"a": 1, "b": 2, "a": 3}"#, &FixOptions::default()),
+        "{\n   \"b\": 2,\n   \"a\": 3\n}"
+    );
+    assert_eq!(
+        fix_with(r#"{"a": "{\"x\": 1, \"x\": 2}"}"#, &options),
+        "{\n   \"a\": {\n      \"x\": 1\n   }\n}"
+    );
+}
+
+#[test]
+fn test_duplicate_keys() {
+    let input = r#"{"a": 1, "b": [2], "a": [3], "b": 4, "a": 5}"#;
+    let fixed = |policy| {
+        fix_with(
+            input,
+            &FixOptions::new().duplicate_keys(policy).minify(true),
+        )
+    };
+
+    assert_eq!(fixed(DuplicateKeys::LastWins), r#"{"b":4,"a":5}"#);
+    assert_eq!(fixed(DuplicateKeys::Error), r#"{"b":4,"a":5}"#);
+    assert_eq!(fixed(DuplicateKeys::FirstWins), r#"{"a":1,"b":[2]}"#);
+    assert_eq!(
+        fixed(DuplicateKeys::KeepAll),
+        r#"{"a":[1,[3],5],"b":[[2],4]}"#
+    );
+    assert_eq!(
+        fixed(DuplicateKeys::Rename),
+        r#"{"a":1,"b":[2],"a#2":[3],"b#2":4,"a#3":5}"#
+    );
+
+    // renamed keys skip names that are taken
+    let options = FixOptions::new()
+        .duplicate_keys(DuplicateKeys::Rename)
+        .minify(true);
+    assert_eq!(
+        fix_with(r#"{"a#2": 0, "a": 1, "a": 2, "a": 3}"#, &options),
+        r#"{"a#2":0,"a":1,"a#3":2,"a#4":3}"#
+    );
+
+    // every occurrence after the first is reported at its key
+    let options = FixOptions::new().duplicate_keys(DuplicateKeys::KeepAll);
+    let report = fix_with_report("{\"a\": 1,\n \"a\": 2, \"a\": 3}", &options);
+    let found: Vec<_> = report
+        .diagnostics
+        .iter()
+        .map(|d| (d.kind, d.line, d.column, d.message.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (
+                RepairKind::DuplicateKey,
+                2,
+                2,
+                "duplicate key \"a\": kept all 2 values"
+            ),
+            (
+                RepairKind::DuplicateKey,
+                2,
+                10,
+                "duplicate key \"a\": kept all 3 values"
+            ),
+        ]
+    );
+
+    let report = fix_with_report(r#"[{"x": 1, "x": 2}, {"x": 3}]"#, &options);
+    assert_eq!(report.diagnostics.len(), 1);
+    assert_eq!(report.diagnostics[0].offset, 10);
+
+    // strict parsing applies the policy, or rejects duplicates
+    let options = FixOptions::new().duplicate_keys(DuplicateKeys::KeepAll);
+    assert_eq!(
+        parse_strict_with(input, &options).unwrap(),
+        fix_value_with(input, &options)
+    );
+    let options = FixOptions::new().duplicate_keys(DuplicateKeys::Error);
+    let err = parse_strict_with(r#"{"a": {"b": 1, "b": x}}"#, &options).unwrap_err();
+    assert_eq!((err.offset, err.expected.as_str()), (15, "a unique key"));
+    assert_eq!(err.found, "duplicate key \"b\"");
+    assert!(parse_strict_with(r#"{"a": {"b": 1}, "b": 2}"#, &options).is_ok());
+    assert_eq!(parse_strict(input).unwrap(), fix_value(input));
+}
+
+#[test]
+fn test_report() {
+    let kinds = |input: &str| {
+        fix_with_report(input, &FixOptions::default())
+            .diagnostics
+            .into_iter()
+            .map(|d| d.kind)
+            .collect::<Vec<_>>()
+    };
+
+    assert!(
+        fix_with_report(
+            r#"{"a": [1, -2.5e3, "x\nA"], "b": {"c": null}, "d": true}"#,
+            &FixOptions::default()
+        )
+        .is_clean()
+    );
+    assert!(fix_with_report("  [false]\n", &FixOptions::default()).is_clean());
+    assert_eq!(kinds(r#"{"a": 1, "a": 2}"#), [RepairKind::DuplicateKey]);
+
+    assert_eq!(kinds(""), [RepairKind::MissingValue]);
+    assert_eq!(kinds("\"abc"), [RepairKind::UnclosedString]);
+    assert_eq!(kinds("[1"), [RepairKind::UnclosedArray]);
+    assert_eq!(kinds("{\"a\": 1"), [RepairKind::UnclosedObject]);
+    assert_eq!(
+        kinds("{\"a\":"),
+        [RepairKind::UnclosedObject, RepairKind::MissingValue]
+    );
+    assert_eq!(kinds(r#"{"a":}"#), [RepairKind::MissingValue]);
+    assert_eq!(kinds(r#"{"a":1 "b":2}"#), [RepairKind::MissingComma]);
+    assert_eq!(kinds("\u{feff}[1,\u{a0}2]"), [RepairKind::Whitespace; 2]);
+    let json5 = FixOptions::new().dialect(Dialect::Json5);
+    assert!(fix_with_report("\u{feff}{a:\u{a0}1}", &json5).is_clean());
+    assert_eq!(kinds(r#""\x""#), [RepairKind::InvalidEscape]);
+    assert_eq!(kinds(r#""\u12XY""#), [RepairKind::InvalidUnicodeEscape]);
+    assert_eq!(kinds("\"a\tb\""), [RepairKind::ControlCharacter]);
+    assert_eq!(kinds("007"), [RepairKind::NumberRepaired]);
+    assert_eq!(
+        kinds("[TRUE, nul]"),
+        [RepairKind::LiteralRepaired, RepairKind::LiteralRepaired]
+    );
+    assert_eq!(kinds("[x]"), [RepairKind::BareWord]);
+    assert_eq!(kinds("[:]"), [RepairKind::InvalidValue]);
+    assert_eq!(kinds("[1 2]"), [RepairKind::MissingComma]);
+    assert_eq!(kinds("{\"key\" 1}"), [RepairKind::MissingColon]);
+    assert_eq!(kinds("[,1,,2,]"), [RepairKind::ExtraComma; 3]);
+    assert_eq!(kinds("{\"a\": 1,}"), [RepairKind::ExtraComma]);
+    assert_eq!(kinds("{123: 1, \"a\": 2}"), [RepairKind::UnquotedKey]);
+    assert_eq!(kinds("{\"a\" = 1}"), [RepairKind::MissingColon]);
+    assert_eq!(kinds("{1 2, \"a\": 2}"), [RepairKind::DroppedKey]);
+    assert_eq!(
+        kinds("[1 abc 2]"),
+        [
+            RepairKind::MissingComma,
+            RepairKind::BareWord,
+            RepairKind::MissingComma
+        ]
+    );
+    assert_eq!(kinds("[1 : 2]"), [RepairKind::SkippedCharacters]);
+    assert_eq!(kinds("true false"), [RepairKind::TrailingCharacters]);
+
+    let report = fix_with_report("{\n  \"a\": [1, 2,\n  \"é\": tru", &FixOptions::default());
+    assert_eq!(report.output, fix("{\n  \"a\": [1, 2,\n  \"é\": tru"));
+
+    let found = report
+        .diagnostics
+        .iter()
+        .map(|d| (d.kind, d.offset, d.line, d.column))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            (RepairKind::UnclosedObject, 0, 1, 1),
+            (RepairKind::UnclosedArray, 9, 2, 8),
+            (RepairKind::SkippedCharacters, 22, 3, 6),
+        ]
+    );
+}
+
+#[test]
+fn test_clean_reports() {
+    // a clean report means the input was valid JSON, whatever is done to a valid document
+    let docs = [
+        r#"{"a": [1, -2.5e3, "x\nA"], "b": {"c": null}, "d": true, "e": [], "f": {}}"#,
+        r#"[{"k": "v", "n": 0}, [1, 2, [3]], "s\u00e9", false, null, -0.5E+2]"#,
+    ];
+    let tokens = [
+        "{", "}", "[", "]", ",", ":", "\"", "'", "\\", "-", "+", ".", "e", "0", "1", "x", "tru",
+        "null", "/", "*", "#", " ", "\t", "\n", "\u{0}", "\u{a0}", "\u{feff}", "é",
+    ];
+
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut random = |n: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % n as u64) as usize
+    };
+    for _ in 0..20_000 {
+        let mut input = docs[random(docs.len())].to_string();
+        for _ in 0..random(3) + 1 {
+            let mut at = random(input.len() + 1);
+            while !input.is_char_boundary(at) {
+                at -= 1;
+            }
+            if at < input.len() && random(2) == 0 {
+                input.remove(at);
+            } else {
+                input.insert_str(at, tokens[random(tokens.len())]);
+            }
+        }
+
+        let report = fix_with_report(&input, &FixOptions::default());
+        assert!(parse_strict(&report.output).is_ok(), "{input:?}");
+        if report.is_clean() {
+            assert!(parse_strict(&input).is_ok(), "{input:?}");
+        }
+    }
+}
+
+#[test]
+fn test_strict() {
+    // Accept cases from JSONTestSuite (y_*).
+    let accepted = [
+        "[[]   ]",
+        "[\"\"]",
+        "[]",
+        "[\"a\"]",
+        "[false]",
+        "[null, 1, \"1\", {}]",
+        "[null]",
+        "[1\n]",
+        " [1]",
+        "[1,null,null,null,2]",
+        "[2] ",
+        "[123e65]",
+        "[0e+1]",
+        "[0e1]",
+        "[ 4]",
+        "[-0.000000000000000000000000000000000000000000000000000000000000000000000000000001]",
+        "[20e1]",
+        "[-0]",
+        "[-123]",
+        "[-1]",
+        "[1E22]",
+        "[1E-2]",
+        "[1E+2]",
+        "[123e45]",
+        "[123.456e78]",
+        "[1e-2]",
+        "[1e+2]",
+        "[123]",
+        "[123.456789]",
+        "{\"asd\":\"sdf\", \"dfg\":\"fgh\"}",
+        "{\"asd\":\"sdf\"}",
+        "{\"a\":\"b\",\"a\":\"c\"}",
+        "{\"a\":\"b\",\"a\":\"b\"}",
+        "{}",
+        "{\"\":0}",
+        "{\"foo\\u0000bar\": 42}",
+        "{ \"min\": -1.0e+28, \"max\": 1.0e+28 }",
+        "{\"a\":[]}",
+        "{\"title\":\"\\u041f\\u043e\\u043b\\u0442\\u043e\\u0440\\u0430 \\u0417\\u0435\\u043c\\u043b\\u0435\\u043a\\u043e\\u043f\\u0430\" }",
+        "{\n\"a\": \"b\"\n}",
+        "[\"\\u0060\\u012a\\u12AB\"]",
+        "[\"\\uD801\\udc37\"]",
+        "[\"\\\"\\\\\\/\\b\\f\\n\\r\\t\"]",
+        "[\"\\\\u0000\"]",
+        "[\"a/*b*/c/*d//e\"]",
+        "[\"\\\\a\"]",
+        "[\"\\u0012\"]",
+        "[\"asd\"]",
+        "[\"\u{ffff}\"]",
+        "[\"\\uDBFF\\uDFFE\"]",
+        "[\"€𝄞\"]",
+        "[\"a\u{7f}a\"]",
+        "false",
+        "42",
+        "-0.1",
+        "null",
+        "\"asd\"",
+        "true",
+        "\"\"",
+        "[\"a\"]\n",
+        "[true]",
+        " [] ",
+    ];
+
+    for input in accepted {
+        assert!(parse_strict(input).is_ok(), "should accept {input:?}");
+    }
+
+    // Reject cases from JSONTestSuite (n_*).
+    let rejected = [
+        "[1 true]",
+        "[\"\": 1]",
+        "[\"\"],",
+        "[,1]",
+        "[1,,2]",
+        "[\"x\",,]",
+        "[\"x\"]]",
+        "[\"\",]",
+        "[\"x\"",
+        "[x",
+        "[3[4]]",
+        "[1:2]",
+        "[,]",
+        "[-]",
+        "[   , \"\"]",
+        "[\"a\",\n4\n,1,",
+        "[1,]",
+        "[1,,]",
+        "[*]",
+        "[\"\"",
+        "[1,",
+        "[{}",
+        "[fals]",
+        "[nul]",
+        "[tru]",
+        "[++1234]",
+        "[+1]",
+        "[+Inf]",
+        "[-01]",
+        "[-1.0.]",
+        "[-2.]",
+        "[-NaN]",
+        "[.-1]",
+        "[.2e-3]",
+        "[0.1.2]",
+        "[0.3e+]",
+        "[0.e1]",
+        "[0E+]",
+        "[1.0e+]",
+        "[1 000.0]",
+        "[1eE2]",
+        "[2.e3]",
+        "[9.e+]",
+        "[Inf]",
+        "[NaN]",
+        "[1+2]",
+        "[0x1]",
+        "[Infinity]",
+        "[012]",
+        "[-Infinity]",
+        "[-012]",
+        "[-.123]",
+        "[1.]",
+        "[.123]",
+        "[\"x\", truth]",
+        "{[: \"x\"}",
+        "{\"x\", null}",
+        "{\"x\"::\"b\"}",
+        "{\"a\":\"a\" 123}",
+        "{key: 'value'}",
+        "{\"a\" b}",
+        "{:\"b\"}",
+        "{\"a\" \"b\"}",
+        "{\"a\":",
+        "{\"a\"",
+        "{1:1}",
+        "{null:null,null:null}",
+        "{'a':0}",
+        "{\"id\":0,}",
+        "{\"a\":\"b\"}/**/",
+        "{a: \"b\"}",
+        "{\"a\": true} \"x\"",
+        " ",
+        "[\"\\uD800\\\"]",
+        "[\"\\x00\"]",
+        "[\"\\\\\\\"]",
+        "[\"\\\t\"]",
+        "[\"\\\"]",
+        "[\"\\u00A\"]",
+        "[\"\\uD834\\uDd\"]",
+        "[\"\\a\"]",
+        "[\\n]",
+        "\"",
+        "['single quote']",
+        "[\"new\nline\"]",
+        "[\"\t\"]",
+        "<.>",
+        "[\"asd]",
+        "[True]",
+        "1]",
+        "[][]",
+        "]",
+        "",
+        "{\"\":",
+        "{",
+        "{\"a\":\"b\"}#{}",
+        "\u{feff}",
+        "[\u{c}]",
+        "å",
+    ];
+
+    for input in rejected {
+        assert!(parse_strict(input).is_err(), "should reject {input:?}");
+        assert!(
+            !fix_with_report(input, &FixOptions::default()).is_clean(),
+            "should repair {input:?}"
+        );
+    }
+
+    assert!(parse_strict(&"[".repeat(100_000)).is_err());
+    assert!(parse_strict(&format!("{}{}", "[".repeat(500), "]".repeat(500))).is_ok());
+
+    assert_eq!(
+        parse_strict("{\"a\": [1, \"x\", {\"b\": null}], \"c\": \"\\u00e9\"}").unwrap(),
+        fix_value("{\"a\": [1, \"x\", {\"b\": null}], \"c\": \"\\u00e9\"}")
+    );
+
+    let err = parse_strict("{\n  \"a\": [1, 2\n  \"b\": tru\n}").unwrap_err();
+    assert_eq!((err.offset, err.line, err.column), (17, 3, 3));
+    assert_eq!(err.expected, "',' or ']'");
+    assert_eq!(err.found, "'\"'");
+
+    let err = parse_strict("[\"é\", 01]").unwrap_err();
+    assert_eq!((err.offset, err.line, err.column), (8, 1, 8));
+    assert_eq!(err.expected, "',' or ']'");
+    assert_eq!(err.found, "'1'");
+
+    let err = parse_strict("{\"a\": 1").unwrap_err();
+    assert_eq!(err.found, "end of input");
+    assert_eq!(
+        err.to_string(),
+        "line 1, column 8: expected ',' or '}', found end of input"
+    );
+
+    let err = parse_strict(r#"["\uDC00"]"#).unwrap_err();
+    assert_eq!((err.offset, err.found.as_str()), (2, "lone surrogate"));
+}
+
+#[test]
+fn test_stream() {
+    let inputs = [
+        "",
+        "   true  ",
+        "nul",
+        "-12.5e+3",
+        "00012",
+        r#""hello \"world\" é😀 \n""#,
+        r#""abc\u12"#,
+        r#"{"a": [1, 2, {"b": null}], "c": "x\\y", "d": tru}"#,
+        r#"{"a":1,"a":2,"b":[[[]]],,"e":{"f":"g"#,
+        r#"[1 abc 2, "x" garbage, {123: 4, "k" v}, .5, -]"#,
+        r#"{"data": "{\"nested\": [1, 2]}", "list": ["[3]"]}"#,
+        "{\"k\": \"line1\nline2\t\"}",
+        "[\"é😀\", 1e, 2E-, --3]   trailing",
+        "{\"a\": [1, 2,\n  \"é\": tru",
+    ];
+
+    for input in inputs {
+        for size in [1, 2, 3, 7, input.len().max(1)] {
+            let chars = input.chars().collect::<Vec<_>>();
+            let mut stream = StreamParser::new();
+            let mut fed = String::new();
+
+            for chunk in chars.chunks(size) {
+                let chunk = chunk.iter().collect::<String>();
+                stream.feed(&chunk);
+                fed.push_str(&chunk);
+
+                assert_eq!(*stream.value(), fix_value(fed.as_str()), "prefix {fed:?}");
+            }
+
+            assert_eq!(stream.finish(), fix_value(input), "input {input:?}");
+        }
+    }
+
+    let options = FixOptions::new()
+        .deserialize_nested(false)
+        .duplicate_keys(DuplicateKeys::FirstWins);
+    let mut stream = StreamParser::with_options(options.clone());
+    for chunk in [r#"{"a": "[1]", "a"#, r#"": 2, "b": 00"#, "7"] {
+        stream.feed(chunk);
+    }
+    assert_eq!(
+        stream.finish(),
+        fix_value_with(r#"{"a": "[1]", "a": 2, "b": 007"#, &options)
+    );
+
+    let mut stream = StreamParser::new();
+    stream.feed("[");
+    for i in 0..1000 {
+        stream.feed(&format!("{{\"id\": {i}, \"name\": \"item {i}\"}}, "));
+    }
+    let value = stream.value();
+    let items = value.as_array().unwrap();
+    assert_eq!(items.len(), 1000);
+    assert_eq!(
+        items[999].get("name").and_then(Json::as_str),
+        Some("item 999")
+    );
+}
+
+#[test]
+fn test_stream_snapshots() {
+    // a snapshot after every character matches fixing the input so far
+    let inputs = [
+        (
+            r#"{"a": [1, {"b": "[2, {\"c\": 3"}, "x"], "a": {"d": [[4]]}, "e": 5}"#,
+            FixOptions::new(),
+        ),
+        (
+            r#"{"k": 1, "k": [2], "j": {"k": 3, "k": 4}, "k": 5}"#,
+            FixOptions::new().duplicate_keys(DuplicateKeys::KeepAll),
+        ),
+        (
+            r#"{"k": 1, "k#2": 2, "k": {"k": 3, "k": 4}}"#,
+            FixOptions::new().duplicate_keys(DuplicateKeys::Rename),
+        ),
+        (
+            r#"{"k": [1], "l": 2, "k": [3, 4]}"#,
+            FixOptions::new().duplicate_keys(DuplicateKeys::FirstWins),
+        ),
+        (
+            "{'s': {'a', 'b'}, 't': (1, (2, 3)), 'u': {1: [None]}}",
+            FixOptions::new().dialect(Dialect::Python),
+        ),
+        (
+            r#"{a: ISODate("2024"), b: [new Date(1, 2), [3]], c: Foo()}"#,
+            FixOptions::new().dialect(Dialect::JavaScript),
+        ),
+        (
+            r#"[[1, [2, [3]]], "[[[4]]]", {"a": [[5]]}]"#,
+            FixOptions::new().max_depth(3),
+        ),
+    ];
+
+    for (input, options) in inputs {
+        let mut stream = StreamParser::with_options(options.clone());
+        let mut fed = String::new();
+        for c in input.chars() {
+            stream.feed(c.encode_utf8(&mut [0; 4]));
+            fed.push(c);
+            assert_eq!(
+                *stream.value(),
+                fix_value_with(fed.as_str(), &options),
+                "prefix {fed:?}"
+            );
+        }
+        assert_eq!(stream.finish(), fix_value_with(input, &options));
+    }
+
+    // any split into two chunks reads the same as the whole input
+    let inputs = [
+        ("\"\\t\\u\u{2028}[Infinity0x", Dialect::Java),
+        ("\"\\\"tru'''{None\\\\u00é  '''\"", Dialect::Json),
+        (
+            "[\"\\u00é\\uD83Dé\\uD83D\\uDE00\", \"\\ué\"]",
+            Dialect::Json,
+        ),
+        (
+            "['\\x4é', '\\xé1', '\\U0001F60é', '\\1é7', b'\\xe9', '''é\\\r\n'''",
+            Dialect::Python,
+        ),
+        (
+            "{a: '\\x4é\\u00é\\0é', b: \"\\\r\n\\uD83Dé\"}",
+            Dialect::Json5,
+        ),
+        ("[\"\\eé\\xé\\u{1F60é}\", :sym]", Dialect::Ruby),
+    ];
+    for (input, dialect) in inputs {
+        let options = FixOptions::new().dialect(dialect);
+        let expected = fix_value_with(input, &options);
+        for (split, _) in input.char_indices() {
+            let mut stream = StreamParser::with_options(options.clone());
+            stream.feed(&input[..split]);
+            assert_eq!(
+                *stream.value(),
+                fix_value_with(&input[..split], &options),
+                "{input:?} split at {split}"
+            );
+            stream.feed(&input[split..]);
+            assert_eq!(stream.finish(), expected, "{input:?} split at {split}");
+        }
+    }
+
+    // snapshots only copy what changed, so one per chunk stays linear
+    let mut input = String::from("[");
+    for i in 0..20_000 {
+        input.push_str(&format!(
+            r#"{{"id": {i}, "tags": ["a", "b"], "msg": "item {i}"}}, "#
+        ));
+    }
+    let mut stream = StreamParser::new();
+    let mut last = 0;
+    for chunk in input.as_bytes().chunks(64) {
+        stream.feed(std::str::from_utf8(chunk).unwrap());
+        let items = stream.value().as_array().unwrap();
+        assert!(items.len() >= last);
+        last = items.len();
+    }
+    assert_eq!(last, 20_000);
+    assert_eq!(stream.finish(), fix_value(input.as_str()));
+}
+
+#[test]
+fn test_borrowed() {
+    use std::borrow::Cow;
+
+    let input = String::from(
+        r#"{"name": "bob", "escaped": "a\"b", "raw": "tab\there", "n": 1.5, "bad": 01, "lit": tru"#,
+    );
+    let json = fix_value_borrowed(&input);
+
+    assert!(matches!(
+        json.get("name"),
+        Some(Json::String(Cow::Borrowed("bob")))
+    ));
+    assert!(matches!(json.get("escaped"), Some(Json::String(Cow::Owned(s))) if s == "a\"b"));
+    assert!(matches!(json.get("raw"), Some(Json::String(Cow::Owned(s))) if s == "tab\there"));
+    assert!(matches!(
+        json.get("n"),
+        Some(Json::Number(Cow::Borrowed("1.5")))
+    ));
+    assert!(matches!(json.get("bad"), Some(Json::Number(Cow::Owned(n))) if n == "1"));
+    assert_eq!(json.get("lit"), Some(&Json::True));
+
+    let Json::Object(obj) = json.clone() else {
+        panic!("expected an object");
+    };
+    assert!(obj.into_iter().all(|(k, _)| matches!(k, Cow::Borrowed(_))));
+
+    // same tree as the owned variant
+    assert_eq!(json, fix_value(input.as_str()));
+    let owned: Json<'static> = json.into_owned();
+    assert_eq!(owned, fix_value(input.as_str()));
+
+    // nested JSON strings without escapes still borrow
+    let input = String::from(r#"{"inner": "[1, 2]"}"#);
+    let json = fix_value_borrowed(&input);
+    assert!(matches!(
+        json.get("inner").and_then(Json::as_array).map(|a| &a[0]),
+        Some(Json::Number(Cow::Borrowed("1")))
+    ));
+
+    let input = String::from(r#"{"k": "v", "e": "\u0041", "n": -2e3}"#);
+    let json = parse_strict(&input).unwrap();
+    assert!(matches!(
+        json.get("k"),
+        Some(Json::String(Cow::Borrowed("v")))
+    ));
+    assert!(matches!(json.get("e"), Some(Json::String(Cow::Owned(s))) if s == "A"));
+    assert!(matches!(
+        json.get("n"),
+        Some(Json::Number(Cow::Borrowed("-2e3")))
+    ));
+}
+
+#[test]
+fn test_io() {
+    use std::io::{self, ErrorKind, Read, Write};
+
+    /// Yields one byte per read, splitting multi-byte characters across reads.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((&first, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    /// Fails after accepting `limit` bytes.
+    struct Full {
+        limit: usize,
+    }
+
+    impl Write for Full {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.limit == 0 {
+                return Err(io::Error::new(ErrorKind::StorageFull, "disk full"));
+            }
+            let n = buf.len().min(self.limit);
+            self.limit -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let inputs = [
+        r#"{"name": "héllo wörld ✓ 🎉", "list": [1, 2.50, tru, "a\"b\u00e9\n"], "nested": "{\"x\": [\"y\"]}""#,
+        "[\"\u{1}\u{1f}\", 1e5, {}, []]",
+        "  ",
+        r#"["#,
+    ];
+
+    for input in inputs {
+        assert_eq!(fix_reader(input.as_bytes()).unwrap(), fix(input));
+        assert_eq!(fix_reader(Trickle(input.as_bytes())).unwrap(), fix(input));
+
+        let mut out = Vec::new();
+        fix_to_writer(input, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), fix(input));
+
+        let mut out = Vec::new();
+        fix_reader_to_writer(Trickle(input.as_bytes()), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), fix(input));
+
+        let mut out = String::new();
+        fix_to_fmt(input, &mut out).unwrap();
+        assert_eq!(out, fix(input));
+    }
+
+    let options = FixOptions::new().indent("\t").deserialize_nested(false);
+    let input = r#"{"a": "[1]", "b": [tr"#;
+    assert_eq!(
+        fix_reader_with(Trickle(input.as_bytes()), &options).unwrap(),
+        fix_with(input, &options)
+    );
+
+    // larger than one read buffer
+    let input = format!("[{}", "\"日本語\", ".repeat(20_000));
+    assert_eq!(fix_reader(input.as_bytes()).unwrap(), fix(input.as_str()));
+
+    // a character cut off at the end is dropped, invalid bytes elsewhere are an error
+    assert_eq!(fix_reader(&b"[\"ab\xe6\x97"[..]).unwrap(), fix("[\"ab"));
+    let err = fix_reader(&b"[\"a\xffb\"]"[..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let err = fix_to_writer("[1, 2, 3]", Full { limit: 4 }).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::StorageFull);
+}
+
+#[test]
+fn test_deep_nesting() {
+    let depth = |mut json: &Json| {
+        let mut depth = 0;
+        while let Some(Json::Array(arr)) = Some(json)
+            && let Some(first) = arr.first()
+        {
+            depth += 1;
+            json = first;
+        }
+        depth
+    };
+
+    // hostile inputs don't overflow the stack and are cut at the default depth
+    let json = fix_value("[".repeat(200_000));
+    assert_eq!(depth(&json), 512);
+    let output = fix("[".repeat(200_000));
+    assert_eq!(
+        (output.matches('[').count(), output.matches("null").count()),
+        (512, 1)
+    );
+    assert_eq!(fix("{\"a\": ".repeat(200_000)).matches('{').count(), 512);
+    assert_eq!(fix("]".repeat(200_000)), "null");
+    assert_eq!(
+        fix(format!("[\"{}\"]", "[".repeat(100_000)))
+            .matches('[')
+            .count(),
+        512
+    );
+
+    let report = fix_with_report("[".repeat(200_000), &FixOptions::default());
+    let too_deep = report
+        .diagnostics
+        .iter()
+        .filter(|d| d.kind == RepairKind::TooDeep)
+        .collect::<Vec<_>>();
+    assert_eq!(too_deep.len(), 1);
+    assert_eq!((too_deep[0].offset, too_deep[0].column), (512, 513));
+
+    // the skipped container ends at its own closing bracket, ignoring brackets in strings
+    let options = FixOptions::new().max_depth(2);
+    assert_eq!(
+        fix_with(r#"[[[1, "]", {"a": "\"}"}], 2], [3], {}]"#, &options),
+        fix(r#"[[null, 2], [3], {}]"#)
+    );
+    assert_eq!(
+        fix_with(r#"{"a": {"b": {"c": 1}, "d": [[]]}, "e": 2}"#, &options),
+        fix(r#"{"a": {"b": null, "d": null}, "e": 2}"#)
+    );
+    assert_eq!(fix_with("[[[[", &options), fix("[[null]]"));
+    assert_eq!(fix_with("[1]", &FixOptions::new().max_depth(0)), "null");
+
+    // the depth is capped, so the deepest value can be cloned, compared and dropped
+    let unlimited = FixOptions::new().max_depth(usize::MAX);
+    let json = fix_value_with("[".repeat(200_000), &unlimited);
+    assert_eq!(depth(&json), 1024);
+    assert_eq!(json.clone().into_owned(), json);
+    assert!(format!("{json:?}").starts_with("Array([Array(["));
+
+    // containers inside nested JSON strings count towards the limit
+    assert_eq!(
+        fix_with(r#"[{"a": "[[1], 2]"}]"#, &options),
+        fix(r#"[{"a": null}]"#)
+    );
+    assert_eq!(
+        fix_with(r#"[{"a": "[[1], 2]"}]"#, &FixOptions::new().max_depth(3)),
+        fix(r#"[{"a": [null, 2]}]"#)
+    );
+
+    // skipping resumes across chunks
+    let input = r#"[[[["x]", "\"[", {"y": []}]], 1], 2]"#;
+    let chars = input.chars().collect::<Vec<_>>();
+    let options = FixOptions::new().max_depth(2);
+    for size in 1..=4 {
+        let mut stream = StreamParser::with_options(options.clone());
+        let mut fed = String::new();
+        for chunk in chars.chunks(size) {
+            let chunk = chunk.iter().collect::<String>();
+            stream.feed(&chunk);
+            fed.push_str(&chunk);
+            assert_eq!(
+                *stream.value(),
+                fix_value_with(fed.as_str(), &options),
+                "prefix {fed:?}"
+            );
+        }
+        assert_eq!(stream.finish(), fix_value(r#"[[null, 1], 2]"#));
+    }
+}
+
+#[test]
+fn test_output_styles() {
+    let input = r#"{"a": [1, {"b": "x\ny", "c": []}], "d": {}, "e": null"#;
+
+    let minified = FixOptions::new().minify(true);
+    assert_eq!(
+        fix_with(input, &minified),
+        r#"{"a":[1,{"b":"x\ny","c":[]}],"d":{},"e":null}"#
+    );
+    assert_eq!(fix_with("[]", &minified), "[]");
+    assert_eq!(fix_with(r#""s"#, &minified), r#""s""#);
+    assert_eq!(fix_value(fix_with(input, &minified)), fix_value(input));
+
+    assert_eq!(
+        fix_with("[1, [2]", &FixOptions::new().indent("\t")),
+        "[\n\t1,\n\t[\n\t\t2\n\t]\n]"
+    );
+    assert_eq!(
+        fix_with(r#"{"a": [1]}"#, &FixOptions::new().indent("  ")),
+        "{\n  \"a\": [\n    1\n  ]\n}"
+    );
+
+    let crlf = FixOptions::new()
+        .line_ending(LineEnding::CrLf)
+        .trailing_newline(true);
+    assert_eq!(
+        fix_with(r#"{"a": [1, 2]}"#, &crlf),
+        "{\r\n   \"a\": [\r\n      1,\r\n      2\r\n   ]\r\n}\r\n"
+    );
+    // line endings inside strings stay escaped
+    assert_eq!(
+        fix_with(r#"["a\r\nb"]"#, &crlf),
+        "[\r\n   \"a\\r\\nb\"\r\n]\r\n"
+    );
+
+    let options = FixOptions::new().minify(true).trailing_newline(true);
+    assert_eq!(fix_with("[1, 2", &options), "[1,2]\n");
+    assert_eq!(fix_with("tru", &options), "true\n");
+    assert_eq!(
+        fix_with_report("[1, 2", &options).output,
+        fix_with("[1, 2", &options)
+    );
+
+    let mut out = Vec::new();
+    crate::fix_to_writer_with(input, &mut out, &options).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), fix_with(input, &options));
+    assert_eq!(
+        crate::fix_reader_with(input.as_bytes(), &options).unwrap(),
+        fix_with(input, &options)
+    );
+
+    // Display keeps the default style
+    assert_eq!(fix_value(input).to_string(), fix(input));
+}
+
+#[test]
+fn test_max_width() {
+    let options = FixOptions::new().max_width(20).indent("  ");
+
+    assert_eq!(fix_with("[[1, 2], [3, 4]]", &options), "[[1, 2], [3, 4]]");
+    assert_eq!(
+        fix_with(r#"{"points": [[1, 2], [3, 4]], "id": 1}"#, &options),
+        "{\n  \"points\": [\n    [1, 2],\n    [3, 4]\n  ],\n  \"id\": 1\n}"
+    );
+
+    // exactly `max_width` columns fit, including the trailing comma
+    let input = r#"[[1, 2222222222], "x"]"#;
+    assert_eq!(
+        fix_with(input, &FixOptions::new().max_width(18).indent("  ")),
+        "[\n  [1, 2222222222],\n  \"x\"\n]"
+    );
+    assert_eq!(
+        fix_with(input, &FixOptions::new().max_width(17).indent("  ")),
+        "[\n  [\n    1,\n    2222222222\n  ],\n  \"x\"\n]"
+    );
+
+    // escapes count towards the width
+    assert_eq!(
+        fix_with(r#"["\n\n\n"]"#, &FixOptions::new().max_width(10)),
+        r#"["\n\n\n"]"#
+    );
+    assert_eq!(
+        fix_with(r#"["\n\n\n"]"#, &FixOptions::new().max_width(9)),
+        "[\n   \"\\n\\n\\n\"\n]"
+    );
+
+    // flat output parses back to the same value
+    let input = r#"{"a": [1, {"b": "c", "d": []}, {}], "e": {"f": [true, null]}}"#;
+    for width in 0..80 {
+        let output = fix_with(input, &FixOptions::new().max_width(width));
+        assert_eq!(
+            fix_value(output.as_str()),
+            fix_value(input),
+            "width {width}"
+        );
+        if width >= 64 {
+            assert_eq!(output.lines().count(), 1);
+        }
+    }
+
+    // a 1,000-point coordinate list takes 1,002 lines instead of 4,002
+    let points = (0..1000)
+        .map(|i| format!("[{i}, {}]", i * 2))
+        .collect::<Vec<_>>();
+    let output = fix_with(
+        format!("[{}]", points.join(", ")),
+        &FixOptions::new().max_width(80),
+    );
+    assert_eq!(output.lines().count(), 1002);
+    assert_eq!(output.lines().nth(1), Some("   [0, 0],"));
+
+    // minify and line endings still apply
+    let options = FixOptions::new()
+        .max_width(10)
+        .line_ending(LineEnding::CrLf);
+    assert_eq!(
+        fix_with("[[1], [2, 3, 4, 5]]", &options),
+        "[\r\n   [1],\r\n   [\r\n      2,\r\n      3,\r\n      4,\r\n      5\r\n   ]\r\n]"
+    );
+    assert_eq!(fix_with("[[1], [2]]", &options.minify(true)), "[[1],[2]]");
+}
+
+#[test]
+fn test_number_format() {
+    use std::borrow::Cow;
+
+    let canonical = FixOptions::new()
+        .number_format(NumberFormat::Canonical)
+        .minify(true);
+    let cases = [
+        ("1.50", "1.5"),
+        ("1E3", "1000"),
+        ("-0.0", "0"),
+        ("0.1e1", "1"),
+        ("123456789012345678901", "123456789012345680000"),
+        ("1e21", "1e+21"),
+        ("1.5E+300", "1.5e+300"),
+        ("0.000001", "0.000001"),
+        ("0.0000001", "1e-7"),
+        ("-12.5e-10", "-1.25e-9"),
+        ("0.1", "0.1"),
+        ("5e-324", "5e-324"),
+        ("1.7976931348623157e308", "1.7976931348623157e+308"),
+        // overflows a double, kept as written
+        ("1e400", "1e400"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(fix_with(input, &canonical), expected, "{input}");
+    }
+    // repaired numbers are canonicalized too
+    assert_eq!(fix_with("[007, 2., .50]", &canonical), "[7,2,0.5]");
+
+    // valid numbers are preserved by default
+    assert_eq!(
+        fix_with("[1.50, 1E3, -0.0]", &FixOptions::new().minify(true)),
+        "[1.50,1E3,-0.0]"
+    );
+
+    // but not a number missing the digits before its exponent
+    for (input, expected) in [("-e0", "-0e0"), ("[-E5, 1]", "[-0E5,1]")] {
+        let report = fix_with_report(input, &FixOptions::new().minify(true));
+        assert_eq!(report.output, expected);
+        assert_eq!(report.diagnostics.len(), 1, "{input}");
+        assert_eq!(report.diagnostics[0].kind, RepairKind::NumberRepaired);
+        assert!(parse_strict(&report.output).is_ok());
+    }
+
+    let safe = FixOptions::new().big_integers_as_strings(true).minify(true);
+    assert_eq!(
+        fix_with(
+            "[9007199254740991, 9007199254740992, -9007199254740992, 00012345678901234567890, 1e300]",
+            &safe
+        ),
+        r#"[9007199254740991,"9007199254740992","-9007199254740992","12345678901234567890",1e300]"#
+    );
+    assert_eq!(
+        fix_with(
+            r#"{"id": 123456789012345678901, "n": 10.0}"#,
+            &safe.number_format(NumberFormat::Canonical)
+        ),
+        r#"{"id":"123456789012345678901","n":10}"#
+    );
+
+    // stringified integers borrow like any string
+    let input = String::from("[12345678901234567890]");
+    let options = FixOptions::new().big_integers_as_strings(true);
+    let json = fix_value_borrowed_with(&input, &options);
+    assert!(matches!(&json, Json::Array(arr) if matches!(arr[0], Json::String(Cow::Borrowed(_)))));
+}
+
+#[test]
+fn test_leading_zeros() {
+    let options = FixOptions::new()
+        .leading_zeros_as_strings(true)
+        .minify(true);
+    assert_eq!(fix_with("007", &options), r#""007""#);
+    assert_eq!(
+        fix_with(r#"{"zip": 00501, "n": 0, "id": 0123, "x": 00}"#, &options),
+        r#"{"zip":"00501","n":0,"id":"0123","x":"00"}"#
+    );
+    // signed, fractional and exponent numbers are still normalized
+    assert_eq!(
+        fix_with("[-007, 00.5, 01e2, 0.5]", &options),
+        "[-7,0.5,1e2,0.5]"
+    );
+    // without the option, the zeros are dropped
+    assert_eq!(
+        fix_with("[00501]", &FixOptions::new().minify(true)),
+        "[501]"
+    );
+
+    let report = fix_with_report("[1, 00501]", &options);
+    assert_eq!(report.diagnostics.len(), 1);
+    assert_eq!(report.diagnostics[0].kind, RepairKind::NumberRepaired);
+    assert_eq!(report.diagnostics[0].offset, 4);
+    assert_eq!(
+        report.diagnostics[0].message,
+        "kept `00501` as a string to preserve its leading zeros"
+    );
+
+    let input = String::from("[00501]");
+    let json = fix_value_borrowed_with(&input, &options);
+    assert_eq!(json.as_array().unwrap()[0].as_str(), Some("00501"));
+}
+
+#[test]
+fn test_number_accessors() {
+    let num = |lex: &'static str| Json::Number(lex.into());
+
+    assert_eq!(fix_value("42").as_i64(), Some(42));
+    assert_eq!(num("-9223372036854775808").as_i64(), Some(i64::MIN));
+    assert_eq!(num("18446744073709551615").as_u64(), Some(u64::MAX));
+    assert_eq!(num("1e3").as_u64(), Some(1000));
+    assert_eq!(num("2.50e1").as_i64(), Some(25));
+    assert_eq!(num("-0").as_u64(), Some(0));
+    assert_eq!(
+        num("-170141183460469231731687303715884105728").as_i128(),
+        Some(i128::MIN)
+    );
+
+    assert_eq!(
+        i64::try_from(&num("9223372036854775808")),
+        Err(NumberError::OutOfRange)
+    );
+    assert_eq!(u64::try_from(&num("-1")), Err(NumberError::OutOfRange));
+    assert_eq!(i64::try_from(&num("1.5")), Err(NumberError::NotAnInteger));
+    assert_eq!(i128::try_from(&num("1e400")), Err(NumberError::OutOfRange));
+    assert_eq!(
+        i64::try_from(&Json::String("1".into())),
+        Err(NumberError::NotANumber)
+    );
+    assert_eq!(i64::try_from(&num("1x")), Err(NumberError::NotANumber));
+
+    assert!(num("1.0").is_integer());
+    assert!(!num("15e-1").is_integer());
+    assert!(!Json::Null.is_integer());
+
+    assert_eq!(num("0.1").as_f64(), Some(0.1));
+    assert_eq!(num("-1.5E3").as_f64(), Some(-1500.0));
+    assert_eq!(num("9007199254740993").as_f64(), None);
+    assert_eq!(
+        f64::try_from(&num("12345678901234567890")),
+        Err(NumberError::PrecisionLoss)
+    );
+    assert_eq!(f64::try_from(&num("1e400")), Err(NumberError::OutOfRange));
+    assert_eq!(
+        f64::try_from(&num("1e-400")),
+        Err(NumberError::PrecisionLoss)
+    );
+
+    let decimal = |mantissa, exponent| Some(Decimal { mantissa, exponent });
+    assert_eq!(num("1.50").as_decimal(), decimal(15, -1));
+    assert_eq!(num("0.15E1").as_decimal(), decimal(15, -1));
+    assert_eq!(num("-1200").as_decimal(), decimal(-12, 2));
+    assert_eq!(num("0.000").as_decimal(), decimal(0, 0));
+    assert_eq!(
+        num("12345678901234567890.12345678901234567890").as_decimal(),
+        decimal(123456789012345678901234567890123456789, -19)
+    );
+    assert_eq!(
+        Decimal::try_from(&num("1234567890123456789012345678901234567890.1")),
+        Err(NumberError::PrecisionLoss)
+    );
+    assert_eq!(
+        Decimal::try_from(&num("1e9999999999")),
+        Err(NumberError::OutOfRange)
+    );
+    assert_eq!(
+        NumberError::PrecisionLoss.to_string(),
+        "number can't be represented exactly"
+    );
+}
+
+#[test]
+fn test_json5() {
+    let options = FixOptions::new().dialect(Dialect::Json5).minify(true);
+    let input = r#"// config
+{
+  unquoted: 'single "quoted"',
+  $id_2: "line \
+continued",
+  /* hex */ hex: [0xFF, -0x10, 0x0, 0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF],
+  dots: [.5, 5., +1, -.5e1],
+  special: [Infinity, -Infinity, +Infinity, NaN],
+  escapes: '\x41\v\0\'\q',
+  "quoted": null, // trailing comment
+}
+"#;
+    let expected = concat!(
+        r#"{"unquoted":"single \"quoted\"","$id_2":"line continued","#,
+        r#""hex":[255,-16,0,340282366920938463463374607431768211455],"#,
+        r#""dots":[0.5,5.0,1,-0.5e1],"special":["Infinity","-Infinity","Infinity","NaN"],"#,
+        r#""escapes":"A\u000b\u0000'q","quoted":null}"#
+    );
+    let report = fix_with_report(input, &options);
+    assert_eq!(report.output, expected);
+    assert!(report.is_clean(), "{:?}", report.diagnostics);
+
+    // the same input streamed one character at a time
+    let mut stream = StreamParser::with_options(options.clone());
+    for c in input.chars() {
+        stream.feed(c.encode_utf8(&mut [0; 4]));
+    }
+    assert_eq!(stream.finish(), fix_value_with(input, &options));
+
+    // identifier keys may use unicode and escapes
+    assert_eq!(
+        fix_with(r"{ключ: 1, abc: 2}", &options),
+        r#"{"ключ":1,"abc":2}"#
+    );
+
+    // invalid JSON5 is still repaired and reported
+    let kinds = |input| {
+        fix_with_report(input, &options)
+            .diagnostics
+            .into_iter()
+            .map(|d| d.kind)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(kinds("[1,,2]"), [RepairKind::ExtraComma]);
+    assert_eq!(kinds("[007]"), [RepairKind::NumberRepaired]);
+    assert_eq!(kinds("[0x]"), [RepairKind::NumberRepaired]);
+    assert_eq!(
+        kinds("[1 /* open"),
+        [RepairKind::UnclosedArray, RepairKind::UnclosedComment]
+    );
+    assert_eq!(fix_with("[1 /* open", &options), "[1]");
+
+    // a sign without a number is not one
+    for (input, expected) in [
+        ("+", r#""+""#),
+        (r#"{"a": +}"#, r#"{"a":"+"}"#),
+        ("[1, +, 2]", r#"[1,"+",2]"#),
+        ("+e5", r#""+e5""#),
+        ("+x", r#""+x""#),
+        ("+ 1", r#""+""#),
+        (r"{\u0+", r#"{"u0":"+"}"#),
+        ("[+Inf, +.5]", r#"["+Inf",0.5]"#),
+    ] {
+        let output = fix_with(input, &options);
+        assert_eq!(output, expected, "{input:?}");
+        assert!(parse_strict(&output).is_ok(), "{input:?}");
+    }
+
+    // the JSON dialect repairs JSON5
+    let report = fix_with_report("['a', 1,]", &FixOptions::new().minify(true));
+    assert_eq!(report.output, r#"["a",1]"#);
+    assert_eq!(
+        report
+            .diagnostics
+            .iter()
+            .map(|d| d.kind)
+            .collect::<Vec<_>>(),
+        [RepairKind::SingleQuotes, RepairKind::ExtraComma]
+    );
+    assert_eq!(kinds("[1,]"), []);
+    assert!(!fix_with_report("[1,]", &FixOptions::new()).is_clean());
+}
+
+#[test]
+fn test_map() {
+    let mut map = Map::new();
+    for i in 0..20 {
+        assert_eq!(
+            map.insert(format!("k{i}"), Json::Number(i.to_string().into())),
+            None
+        );
+    }
+    assert_eq!(map.len(), 20);
+    assert_eq!(map.get("k7").and_then(Json::as_number), Some("7"));
+
+    // replacing keeps the position, unless asked to move the key last
+    assert_eq!(map.insert("k0", Json::Null), Some(Json::Number("0".into())));
+    assert_eq!(map.keys().next(), Some("k0"));
+    assert_eq!(
+        map.insert_last("k1", Json::True),
+        Some(Json::Number("1".into()))
+    );
+    assert_eq!(map.keys().last(), Some("k1"));
+    assert_eq!(map.keys().nth(1), Some("k2"));
+
+    // removing keeps the others in order
+    for i in (2..20).step_by(2) {
+        assert!(map.remove(&format!("k{i}")).is_some());
+    }
+    assert_eq!(map.remove("k2"), None);
+    assert_eq!(
+        map.keys().collect::<Vec<_>>(),
+        [
+            "k0", "k3", "k5", "k7", "k9", "k11", "k13", "k15", "k17", "k19", "k1"
+        ]
+    );
+    assert_eq!(map.iter().len(), 11);
+    assert!(
+        (3..20)
+            .step_by(2)
+            .all(|i| map.contains_key(&format!("k{i}")))
+    );
+
+    // equality depends on the order
+    let a: Map = [("x", Json::Null), ("y", Json::True)].into_iter().collect();
+    let b: Map = [("y", Json::True), ("x", Json::Null)].into_iter().collect();
+    assert_ne!(a, b);
+    assert_eq!(a, a.clone());
+
+    // many keys, each replaced many times
+    let mut input = String::from("{");
+    for i in 0..200_000 {
+        input.push_str(&format!(r#""k{}": {i},"#, i % 1000));
+    }
+    let json = fix_value(input.as_str());
+    let obj = json.as_object().unwrap();
+    assert_eq!(obj.len(), 1000);
+    assert_eq!(obj.keys().next(), Some("k0"));
+    assert_eq!(obj.get("k999").and_then(Json::as_number), Some("199999"));
+
+    let options = FixOptions::new().duplicate_keys(DuplicateKeys::FirstWins);
+    let json = fix_value_with(input.as_str(), &options);
+    assert_eq!(json.get("k999").and_then(Json::as_number), Some("999"));
+    assert_eq!(
+        parse_strict(&format!("{}}}", &input[..input.len() - 1])).unwrap(),
+        fix_value(input.as_str())
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    use serde::Deserialize;
+    use std::borrow::Cow;
+
+    use crate::{fix_to, fix_to_with, fix_value_borrowed};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Status {
+        Active,
+        Banned { reason: String },
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct User {
+        id: u64,
+        name: String,
+        score: f64,
+        tags: Vec<String>,
+        email: Option<String>,
+        status: Status,
+    }
+
+    let user: User = fix_to(
+        r#"{"id": 7, "name": "alice", "score": -1.5, "tags": ["a", "b",], "email": nul, "status": "Active""#,
+    )
+    .unwrap();
+    assert_eq!(
+        user,
+        User {
+            id: 7,
+            name: String::from("alice"),
+            score: -1.5,
+            tags: vec![String::from("a"), String::from("b")],
+            email: None,
+            status: Status::Active,
+        }
+    );
+
+    // nested JSON strings are deserialized before reaching the target type
+    let user: User = fix_to(
+        r#"{"id": 1, "name": "bob", "score": 2, "tags": "[\"x\"]", "email": "b@x", "status": {"Banned": {"reason": "spam"}}}"#,
+    )
+    .unwrap();
+    assert_eq!(user.tags, vec![String::from("x")]);
+    assert_eq!(user.email.as_deref(), Some("b@x"));
+    assert_eq!(
+        user.status,
+        Status::Banned {
+            reason: String::from("spam")
+        }
+    );
+
+    let err = fix_to::<User>(r#"{"id": "seven", "name": "carol""#).unwrap_err();
+    assert_eq!(err.message, r#"invalid type: string "seven", expected u64"#);
+    assert_eq!(err.diagnostics.len(), 1);
+    assert_eq!(err.diagnostics[0].kind, RepairKind::UnclosedObject);
+    assert_eq!(err.to_string(), err.message);
+
+    let err = fix_to::<Vec<u8>>("[1, 2, 300]").unwrap_err();
+    assert!(err.diagnostics.is_empty());
+
+    let options = FixOptions::new().deserialize_nested(false);
+    let tags: Vec<String> = fix_to_with(r#"["[1]", "x"]"#, &options).unwrap();
+    assert_eq!(tags, ["[1]", "x"]);
+
+    // user types can borrow from a borrowed tree
+    let input = String::from(r#"{"name": "dave""#);
+    let json = fix_value_borrowed(&input);
+    #[derive(Deserialize)]
+    struct Borrowed<'a> {
+        name: &'a str,
+    }
+    assert_eq!(Borrowed::deserialize(json).unwrap().name, "dave");
+
+    // Serialize keeps key order and number types
+    let json =
+        fix_value(r#"{"b": 1, "a": [true, null, -2, 1.5e3, "s"], "big": 123456789012345678901"#);
+    assert_eq!(
+        serde_json::to_string(&json).unwrap(),
+        r#"{"b":1,"a":[true,null,-2,1500.0,"s"],"big":123456789012345678901}"#
+    );
+
+    // integers past 64 bits keep their digits both ways
+    let json = fix_value("[98765432109876543210, -98765432109876543210]");
+    assert_eq!(
+        serde_json::to_string(&json).unwrap(),
+        "[98765432109876543210,-98765432109876543210]"
+    );
+    assert_eq!(Json::deserialize(json.clone()).unwrap(), json);
+    assert_eq!(
+        <(u128, i128)>::deserialize(json).unwrap(),
+        (98765432109876543210, -98765432109876543210)
+    );
+
+    // Deserialize from other formats, and round trip through the Deserializer
+    let input = r#"{"k": "v", "n": [1, -2, 0.5], "o": {"x": null}, "k": false}"#;
+    let json: Json = serde_json::from_str(input).unwrap();
+    assert!(matches!(json.get("o"), Some(Json::Object(_))));
+    assert_eq!(json.get("k"), Some(&Json::False));
+    assert_eq!(
+        json.entries().unwrap().map(|(k, _)| k).collect::<Vec<_>>(),
+        ["n", "o", "k"]
+    );
+    let Json::Object(obj) = json.clone() else {
+        panic!("expected an object");
+    };
+    assert!(obj.into_iter().all(|(k, _)| matches!(k, Cow::Borrowed(_))));
+
+    let fixed = fix_value(r#"{"a": [1, "two", {"c": tru}], "d": -0.25"#);
+    assert_eq!(Json::deserialize(fixed.clone()).unwrap(), fixed);
+
+    // serializing recurses, up to the highest `max_depth`
+    let options = FixOptions::new().max_depth(usize::MAX);
+    let deep = fix_value_with("[".repeat(200_000), &options);
+    assert_eq!(
+        serde_json::to_string(&deep).unwrap(),
+        format!("{}null{}", "[".repeat(1024), "]".repeat(1024))
+    );
+}
+
+#[test]
+fn test_unquoted_keys() {
+    let options = FixOptions::new().minify(true);
+    let input = "{name: \"bob\", age = 3, 42: true, content-type: \"json\", $id:1}";
+    let report = fix_with_report(input, &options);
+    assert_eq!(
+        report.output,
+        r#"{"name":"bob","age":3,"42":true,"content-type":"json","$id":1}"#
+    );
+    assert_eq!(
+        report
+            .diagnostics
+            .iter()
+            .map(|d| (d.kind, d.offset))
+            .collect::<Vec<_>>(),
+        [
+            (RepairKind::UnquotedKey, 1),
+            (RepairKind::UnquotedKey, 14),
+            (RepairKind::MissingColon, 18),
+            (RepairKind::UnquotedKey, 23),
+            (RepairKind::UnquotedKey, 33),
+            (RepairKind::UnquotedKey, 55),
+        ]
+    );
+
+    // the same input streamed one character at a time
+    let mut stream = StreamParser::with_options(options.clone());
+    for c in input.chars() {
+        stream.feed(c.encode_utf8(&mut [0; 4]));
+    }
+    assert_eq!(stream.finish(), fix_value_with(input, &options));
+
+    // a word not followed by `:` or `=` is not a key
+    assert_eq!(fix_with("{1}", &options), "{}");
+    assert_eq!(fix_with("{a b, \"c\": 1}", &options), r#"{"c":1}"#);
+    // a truncated key is kept, like a truncated string key
+    assert_eq!(
+        fix_with("{\"a\": 1, name", &options),
+        r#"{"a":1,"name":null}"#
+    );
+
+    // valid JSON5 keys are not reported
+    let json5 = options.dialect(Dialect::Json5);
+    assert!(fix_with_report("{name: 1}", &json5).is_clean());
+}
+
+#[test]
+fn test_comments() {
+    let options = FixOptions::new().minify(true);
+    let kinds = |input: &str, options: &FixOptions| {
+        fix_with_report(input, options)
+            .diagnostics
+            .iter()
+            .map(|d| d.kind)
+            .collect::<Vec<_>>()
+    };
+    let input = "// tsconfig\n{\n  \"strict\": true, // not false\n  /* 2 \"a\": t */ \"target\": \"es2022\",\n  # hash 1\n  \"lib\": [\"dom\" /**/, 3]\n}\n";
+    let report = fix_with_report(input, &options);
+    assert_eq!(
+        report.output,
+        r#"{"strict":true,"target":"es2022","lib":["dom",3]}"#
+    );
+    assert_eq!(
+        report
+            .diagnostics
+            .iter()
+            .map(|d| (d.kind, d.line))
+            .collect::<Vec<_>>(),
+        [
+            (RepairKind::Comment, 1),
+            (RepairKind::Comment, 3),
+            (RepairKind::Comment, 4),
+            (RepairKind::Comment, 5),
+            (RepairKind::Comment, 6),
+        ]
+    );
+
+    // the same input streamed one character at a time
+    let mut stream = StreamParser::with_options(options.clone());
+    for c in input.chars() {
+        stream.feed(c.encode_utf8(&mut [0; 4]));
+    }
+    assert_eq!(stream.finish(), fix_value_with(input, &options));
+
+    assert_eq!(fix_with("[1 /* 2, 3", &options), "[1]");
+    assert_eq!(
+        kinds("[1 /* 2, 3", &options),
+        [
+            RepairKind::UnclosedArray,
+            RepairKind::Comment,
+            RepairKind::UnclosedComment
+        ]
+    );
+    // a lone `/` is not a comment, and is kept like other bare words
+    assert_eq!(
+        kinds("[1 / 2]", &options),
+        [
+            RepairKind::MissingComma,
+            RepairKind::BareWord,
+            RepairKind::MissingComma
+        ]
+    );
+
+    // where a value belongs, `#` starts a bare word unless it starts a line
+    for (input, expected) in [
+        (r##"{"b": #fff}"##, r##"{"b":"#fff"}"##),
+        (r##"["#", #fff, 1 # one"##, r##"["#","#fff",1]"##),
+        ("[\n  # one\n  1\n]", "[1]"),
+    ] {
+        assert_eq!(fix_with(input, &options), expected, "{input:?}");
+
+        let mut stream = StreamParser::with_options(options.clone());
+        for c in input.chars() {
+            stream.feed(c.encode_utf8(&mut [0; 4]));
+        }
+        assert_eq!(
+            stream.finish(),
+            fix_value_with(input, &options),
+            "{input:?}"
+        );
+    }
+    assert_eq!(kinds(r##"{"b": #fff}"##, &options), [RepairKind::BareWord]);
+
+    // `//` and `/* */` are valid JSON5, `#` is not
+    let json5 = options.dialect(Dialect::Json5);
+    assert_eq!(kinds("[1, // one\n 2 /* two */]", &json5), []);
+    assert_eq!(kinds("[1 # one\n]", &json5), [RepairKind::Comment]);
+}
+
+#[test]
+fn test_python() {
+    let options = FixOptions::new().dialect(Dialect::Python).minify(true);
+    let input = r#"{'id': 5, 'ok': True, 'tags': ('a', "b",), 'x': None, 'no': False, 1: {2, 3}, None: set(), 'empty': (), 's': {'x'}}"#;
+    let report = fix_with_report(input, &options);
+    assert_eq!(
+        report.output,
+        r#"{"id":5,"ok":true,"tags":["a","b"],"x":null,"no":false,"1":[2,3],"null":[],"empty":[],"s":["x"]}"#
+    );
+    assert!(report.is_clean());
+
+    let input = r#"['''multi
+line 'quoted' ''', """say "hi" """, 'it\'s\ttab\a\101\x41é\U0001F600\d', r'\d+\'', b'caf\xc3\xa9', b'\xff\x00', # comment
+ u'x',]"#;
+    let report = fix_with_report(input, &options);
+    assert_eq!(
+        report.output,
+        r#"["multi\nline 'quoted' ","say \"hi\" ","it's\ttab\u0007AAé😀\\d","\\d+\\'","café","ÿ\u0000","x"]"#
+    );
+    assert!(report.is_clean());
+
+    // the same inputs streamed one character at a time
+    for input in [input, "{'a': (1, (2,)), 'b': {'c'}, 'd': b'\\x41'}"] {
+        let mut stream = StreamParser::with_options(options.clone());
+        for c in input.chars() {
+            stream.feed(c.encode_utf8(&mut [0; 4]));
+        }
+        assert_eq!(stream.finish(), fix_value_with(input, &options));
+    }
+
+    assert_eq!(fix_with("[''", &options), r#"[""]"#);
+    assert_eq!(fix_with("['''ab''", &options), r#"["ab''"]"#);
+    assert_eq!(fix_with("{'a': (1, 2", &options), r#"{"a":[1,2]}"#);
+
+    // tuple keys keep their text, `frozenset` is a set, and non-finite floats follow
+    // `non_finite`
+    let input = "{(1, 'a)'): 'x', 'f': frozenset({1, 2}), 'e': frozenset(), 'n': [inf, -inf, nan], 's': {(1, 2), (3,)}}";
+    let report = fix_with_report(input, &options);
+    assert_eq!(
+        report.output,
+        r#"{"(1, 'a)')":"x","f":[1,2],"e":[],"n":["Infinity","-Infinity","NaN"],"s":[[1,2],[3]]}"#
+    );
+    assert!(report.is_clean(), "{:?}", report.diagnostics);
+    assert_eq!(
+        fix_with(
+            "[inf, -inf, nan, info]",
+            &options.clone().non_finite(NonFinite::Null)
+        ),
+        r#"[null,null,null,"info"]"#
+    );
+    let mut stream = StreamParser::with_options(options.clone());
+    for c in input.chars() {
+        stream.feed(c.encode_utf8(&mut [0; 4]));
+    }
+    assert_eq!(stream.finish(), fix_value_with(input, &options));
+
+    // other dialects keep parentheses and `#` comments as errors
+    assert_eq!(
+        fix_with("[(1)]", &FixOptions::new().minify(true)),
+        r#"["(1)"]"#
+    );
+}
+
+#[test]
+fn test_javascript() {
+    let options = FixOptions::new().dialect(Dialect::JavaScript).minify(true);
+    let input = r#"{a: undefined, b: NaN, c: new Date("2024-01-01"), d: /x+[/]\//g, _id: ObjectId('5f1d'), e: ISODate ("2024-01-02T00:00:00Z"), n: NumberLong(42), f: [undefined, -Infinity], g: new Date(), h: Timestamp(1, 2),}"#;
+    let report = fix_with_report(input, &options);
+    assert_eq!(
+        report.output,
+        r#"{"a":null,"b":"NaN","c":"2024-01-01","d":"/x+[/]\\//g","_id":"5f1d","e":"2024-01-02T00:00:00Z","n":42,"f":[null,"-Infinity"],"g":null,"h":[1,2]}"#
+    );
+    assert!(report.is_clean());
+
+    let omit = options
+        .clone()
+        .undefined(Undefined::Omit)
+        .non_finite(NonFinite::Null);
+    assert_eq!(
+        fix_with(input, &omit),
+        r#"{"b":null,"c":"2024-01-01","d":"/x+[/]\\//g","_id":"5f1d","e":"2024-01-02T00:00:00Z","n":42,"f":[null,null],"g":null,"h":[1,2]}"#
+    );
+    assert_eq!(fix_with("undefined", &omit), "null");
+
+    // the same input streamed one character at a time
+    for options in [&options, &omit] {
+        let mut stream = StreamParser::with_options(options.clone());
+        for c in input.chars() {
+            stream.feed(c.encode_utf8(&mut [0; 4]));
+        }
+        assert_eq!(stream.finish(), fix_value_with(input, options));
+    }
+
+    // truncated calls and regular expressions are closed
+    assert_eq!(fix_with("[ISODate(\"2024", &options), r#"["2024"]"#);
+    assert_eq!(fix_with("[/ab", &options), r#"["/ab"]"#);
+    // other words are still repaired
+    assert_eq!(fix_with("[nul, True]", &options), "[null,true]");
+
+    // `NaN` and `Infinity` are the same in JSON5, which has no `undefined`
+    let json5 = FixOptions::new()
+        .dialect(Dialect::Json5)
+        .non_finite(NonFinite::Null)
+        .minify(true);
+    assert_eq!(fix_with("[NaN, +Infinity]", &json5), "[null,null]");
+    assert_eq!(fix_with("[undefined]", &json5), "[null]");
+}
+
+#[test]
+fn test_map_dialects() {
+    let check = |dialect: Dialect, input: &str, expected: &str| {
+        let options = FixOptions::new().dialect(dialect).minify(true);
+        let report = fix_with_report(input, &options);
+        assert_eq!(report.output, expected, "{input}");
+        assert!(report.is_clean(), "{input}: {:?}", report.diagnostics);
+
+        // the same input streamed one character at a time
+        let mut stream = StreamParser::with_options(options.clone());
+        for c in input.chars() {
+            stream.feed(c.encode_utf8(&mut [0; 4]));
+        }
+        assert_eq!(stream.finish(), fix_value_with(input, &options), "{input}");
+    };
+
+    check(
+        Dialect::Ruby,
+        r#"{"a"=>1, :b=>nil, c: [:d, "e\e"], "f" => {:g => 2.5}}"#,
+        r#"{"a":1,"b":null,"c":["d","e\u001b"],"f":{"g":2.5}}"#,
+    );
+    check(
+        Dialect::Java,
+        "{a=1, b=[x, y], name=John Smith, zip=00501, n=null, ok=true, url=http://x/#top, m={k=-2.5E3}}",
+        r#"{"a":1,"b":["x","y"],"name":"John Smith","zip":"00501","n":null,"ok":true,"url":"http://x/#top","m":{"k":-2.5E3}}"#,
+    );
+    check(
+        Dialect::Go,
+        "map[a:1 b:[x y] c:map[d:<nil> e:true] f:+Inf g:{Name:Bob Age:3}]",
+        r#"{"a":1,"b":["x","y"],"c":{"d":null,"e":true},"f":"Infinity","g":{"Name":"Bob","Age":3}}"#,
+    );
+    check(Dialect::Go, "[map[] []]", "[{},[]]");
+
+    // objects printed by `inspect`, and quoted and predicate symbols
+    check(
+        Dialect::Ruby,
+        r#"{:a=>#<Foo id: 1>, :b=>2, :c=>[#<Bar:0x01 @h={:x=>1}, @s="a>b">]}"#,
+        r##"{"a":"#<Foo id: 1>","b":2,"c":["#<Bar:0x01 @h={:x=>1}, @s=\"a>b\">"]}"##,
+    );
+    check(
+        Dialect::Ruby,
+        r#"{:"quoted sym"=>2}"#,
+        r#"{"quoted sym":2}"#,
+    );
+    check(Dialect::Ruby, "[:a, :b?, :c!]", r#"["a","b?","c!"]"#);
+
+    // empty values are empty strings, and leave the rest of the container
+    check(Dialect::Java, "{a=, b=1}", r#"{"a":"","b":1}"#);
+    check(Dialect::Java, "[{a=}, {b=2}]", r#"[{"a":""},{"b":2}]"#);
+    check(Dialect::Go, "map[a: b:2]", r#"{"a":"","b":2}"#);
+    check(Dialect::Go, "[map[a:] map[b:2]]", r#"[{"a":""},{"b":2}]"#);
+    check(Dialect::Go, "{Name: Age:3}", r#"{"Name":"","Age":3}"#);
+    check(Dialect::Go, "map[a: b]", r#"{"a":"b"}"#);
+
+    // values run across spaces up to the next key
+    check(
+        Dialect::Go,
+        "map[msg:request failed code:500 ok:true]",
+        r#"{"msg":"request failed","code":500,"ok":true}"#,
+    );
+    check(
+        Dialect::Go,
+        "map[a:John Smith b:2 c:3]",
+        r#"{"a":"John Smith","b":2,"c":3}"#,
+    );
+    check(
+        Dialect::Go,
+        "{Name:John Smith Age:3}",
+        r#"{"Name":"John Smith","Age":3}"#,
+    );
+    check(
+        Dialect::Java,
+        "{msg=request failed, code=500}",
+        r#"{"msg":"request failed","code":500}"#,
+    );
+    check(
+        Dialect::Go,
+        "map[a:[x y] b:x y ]",
+        r#"{"a":["x","y"],"b":"x y"}"#,
+    );
+
+    // the separators are repairs in the other dialects
+    let options = FixOptions::new().minify(true);
+    let report = fix_with_report(r#"{"a"=>1}"#, &options);
+    assert_eq!(report.output, r#"{"a":1}"#);
+    assert_eq!(
+        report
+            .diagnostics
+            .iter()
+            .map(|d| d.kind)
+            .collect::<Vec<_>>(),
+        [RepairKind::MissingColon]
+    );
+    assert_eq!(
+        fix_with_report("{a=1}", &options.clone().dialect(Dialect::Ruby))
+            .diagnostics
+            .iter()
+            .map(|d| d.kind)
+            .collect::<Vec<_>>(),
+        [RepairKind::MissingColon]
+    );
+}
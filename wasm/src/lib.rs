@@ -1,6 +1,127 @@
 use wasm_bindgen::prelude::*;
 
+/// Output style for [`fix`], built with chained setters:
+/// `fix(input, new FixOptions().indent("  ").trailingNewline(true))`.
 #[wasm_bindgen]
-pub fn fix(input: &str) -> String {
-    fjson_core::fix(input)
+#[derive(Default)]
+pub struct FixOptions(fjson_core::FixOptions);
+
+#[wasm_bindgen]
+impl FixOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Syntax of the input: `"json"` (default), `"json5"`, `"javascript"`, `"python"`,
+    /// `"ruby"`, `"java"` or `"go"`.
+    pub fn dialect(self, name: &str) -> Result<FixOptions, JsError> {
+        let dialect = match name {
+            "json" => fjson_core::Dialect::Json,
+            "json5" => fjson_core::Dialect::Json5,
+            "javascript" => fjson_core::Dialect::JavaScript,
+            "python" => fjson_core::Dialect::Python,
+            "ruby" => fjson_core::Dialect::Ruby,
+            "java" => fjson_core::Dialect::Java,
+            "go" => fjson_core::Dialect::Go,
+            _ => return Err(JsError::new(&format!("unknown dialect: {name}"))),
+        };
+        Ok(Self(self.0.dialect(dialect)))
+    }
+
+    /// Leave out object members whose value is `undefined` (`"javascript"` dialect).
+    #[wasm_bindgen(js_name = omitUndefined)]
+    pub fn omit_undefined(self, enabled: bool) -> Self {
+        let undefined = if enabled {
+            fjson_core::Undefined::Omit
+        } else {
+            fjson_core::Undefined::Null
+        };
+        Self(self.0.undefined(undefined))
+    }
+
+    /// Turn `NaN` and `Infinity` into `null` instead of strings.
+    #[wasm_bindgen(js_name = nonFiniteAsNull)]
+    pub fn non_finite_as_null(self, enabled: bool) -> Self {
+        let non_finite = if enabled {
+            fjson_core::NonFinite::Null
+        } else {
+            fjson_core::NonFinite::String
+        };
+        Self(self.0.non_finite(non_finite))
+    }
+
+    /// Leave out bare words that aren't literals (`ok`) instead of quoting them.
+    #[wasm_bindgen(js_name = dropBareWords)]
+    pub fn drop_bare_words(self, enabled: bool) -> Self {
+        let bare_words = if enabled {
+            fjson_core::BareWords::Drop
+        } else {
+            fjson_core::BareWords::String
+        };
+        Self(self.0.bare_words(bare_words))
+    }
+
+    /// Print on a single line without whitespace.
+    pub fn minify(self, enabled: bool) -> Self {
+        Self(self.0.minify(enabled))
+    }
+
+    /// Indentation for each nesting level (default: three spaces).
+    pub fn indent(self, indent: &str) -> Self {
+        Self(self.0.indent(indent))
+    }
+
+    /// Keep arrays and objects that fit within `width` columns on one line.
+    #[wasm_bindgen(js_name = maxWidth)]
+    pub fn max_width(self, width: usize) -> Self {
+        Self(self.0.max_width(width))
+    }
+
+    /// End the output with a line ending.
+    #[wasm_bindgen(js_name = trailingNewline)]
+    pub fn trailing_newline(self, enabled: bool) -> Self {
+        Self(self.0.trailing_newline(enabled))
+    }
+
+    /// Keep unquoted digits with leading zeros (ZIP codes, IDs) as strings: `00501` → `"00501"`.
+    #[wasm_bindgen(js_name = leadingZerosAsStrings)]
+    pub fn leading_zeros_as_strings(self, enabled: bool) -> Self {
+        Self(self.0.leading_zeros_as_strings(enabled))
+    }
+
+    /// Print numbers the way JavaScript does: shortest round-trip form (`1.50` → `1.5`).
+    #[wasm_bindgen(js_name = canonicalNumbers)]
+    pub fn canonical_numbers(self, enabled: bool) -> Self {
+        let format = if enabled {
+            fjson_core::NumberFormat::Canonical
+        } else {
+            fjson_core::NumberFormat::Preserve
+        };
+        Self(self.0.number_format(format))
+    }
+
+    /// Quote integers beyond ±2^53 (e.g. IDs), so `JSON.parse` doesn't round them.
+    #[wasm_bindgen(js_name = bigIntegersAsStrings)]
+    pub fn big_integers_as_strings(self, enabled: bool) -> Self {
+        Self(self.0.big_integers_as_strings(enabled))
+    }
+
+    /// Use `\r\n` instead of `\n` line endings.
+    pub fn crlf(self, enabled: bool) -> Self {
+        let line_ending = if enabled {
+            fjson_core::LineEnding::CrLf
+        } else {
+            fjson_core::LineEnding::Lf
+        };
+        Self(self.0.line_ending(line_ending))
+    }
+}
+
+#[wasm_bindgen]
+pub fn fix(input: &str, options: Option<FixOptions>) -> String {
+    match options {
+        Some(options) => fjson_core::fix_with(input, &options.0),
+        None => fjson_core::fix(input),
+    }
 }